@@ -0,0 +1,4 @@
+pub mod ping;
+pub mod serve;
+pub mod simulate;
+pub mod swap;
@@ -15,17 +15,24 @@ impl PingCommand {
     }
     
     pub async fn execute(&self) -> Result<ExecutorResult> {
-        info!("Executing ping command to {}", self.rpc_url);
-        
-        let start_time = Instant::now();
-        
         // Create RPC client with timeout
         let client = RpcClient::new_with_timeout(
             self.rpc_url.clone(),
             Duration::from_secs(self.timeout),
         );
-        
-        match self.ping_rpc(&client).await {
+
+        self.execute_with_client(&client).await
+    }
+
+    /// Runs the ping against an already-constructed client, so callers that
+    /// hold a long-lived `RpcClient` (e.g. the `Serve` command) can reuse it
+    /// across requests instead of paying per-call client setup.
+    pub async fn execute_with_client(&self, client: &RpcClient) -> Result<ExecutorResult> {
+        info!("Executing ping command to {}", self.rpc_url);
+
+        let start_time = Instant::now();
+
+        match self.ping_rpc(client).await {
             Ok(slot) => {
                 let duration = start_time.elapsed();
                 info!("Ping successful - Current slot: {}, Duration: {:?}", slot, duration);
@@ -42,8 +49,12 @@ impl PingCommand {
                         format!("Response time: {:?}", duration),
                     ]),
                     expected_out: None,
+                    simulated_out: None,
+                    realized_price_impact_pct: None,
                     compute_units_used: None,
                     idempotency_key: None,
+                    priority_fee_micro_lamports: None,
+                    max_input_amount: None,
                 })
             }
             Err(e) => {
@@ -60,8 +71,12 @@ impl PingCommand {
                         format!("Error: {}", e),
                     ]),
                     expected_out: None,
+                    simulated_out: None,
+                    realized_price_impact_pct: None,
                     compute_units_used: None,
                     idempotency_key: None,
+                    priority_fee_micro_lamports: None,
+                    max_input_amount: None,
                 })
             }
         }
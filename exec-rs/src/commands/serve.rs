@@ -0,0 +1,262 @@
+use crate::commands::{ping::PingCommand, simulate::SimulateCommand, swap::SwapCommand};
+use crate::jupiter::JupiterSwapMode;
+use anyhow::Result;
+use axum::{extract::State, routing::post, Json, Router};
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use std::sync::Arc;
+
+/// Starts a persistent JSON-RPC server exposing `ping`, `simulate`, and
+/// `swap` over a single shared `RpcClient`, so callers issuing many trades
+/// don't pay process-startup and client-setup cost on every request. Each
+/// method's params mirror the corresponding `Commands` variant, and its
+/// result is the same `ExecutorResult` the CLI prints.
+pub struct ServeCommand {
+    bind_addr: String,
+    rpc_url: String,
+    idempotency_dir: String,
+}
+
+/// State shared across every request the server handles. `idempotency_dir`
+/// is fixed at server startup rather than taken as a per-request param,
+/// since RPC callers are not trusted to name an arbitrary directory on the
+/// server's filesystem.
+#[derive(Clone)]
+struct ServeState {
+    client: Arc<RpcClient>,
+    idempotency_dir: Arc<String>,
+}
+
+impl ServeCommand {
+    pub fn new(bind_addr: String, rpc_url: String, idempotency_dir: String) -> Self {
+        Self {
+            bind_addr,
+            rpc_url,
+            idempotency_dir,
+        }
+    }
+
+    pub async fn execute(&self) -> Result<()> {
+        let client = Arc::new(RpcClient::new_with_commitment(
+            self.rpc_url.clone(),
+            CommitmentConfig::confirmed(),
+        ));
+        let state = ServeState {
+            client,
+            idempotency_dir: Arc::new(self.idempotency_dir.clone()),
+        };
+
+        let app = Router::new()
+            .route("/rpc", post(handle_rpc))
+            .with_state(state);
+
+        info!("JSON-RPC server listening on {}", self.bind_addr);
+        let listener = tokio::net::TcpListener::bind(&self.bind_addr).await?;
+        axum::serve(listener, app).await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Deserialize)]
+struct RpcRequest {
+    id: Value,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(Serialize)]
+struct RpcResponse {
+    jsonrpc: &'static str,
+    id: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<crate::ExecutorResult>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+async fn handle_rpc(
+    State(state): State<ServeState>,
+    Json(request): Json<RpcRequest>,
+) -> Json<RpcResponse> {
+    let method = request.method.clone();
+
+    let outcome = match method.as_str() {
+        "ping" => dispatch_ping(&state.client, request.params).await,
+        "simulate" => dispatch_simulate(&state.client, request.params).await,
+        "swap" => dispatch_swap(&state.client, &state.idempotency_dir, request.params).await,
+        other => Err(anyhow::anyhow!("unknown method '{}'", other)),
+    };
+
+    let response = match outcome {
+        Ok(result) => RpcResponse {
+            jsonrpc: "2.0",
+            id: request.id,
+            result: Some(result),
+            error: None,
+        },
+        Err(e) => {
+            error!("RPC method '{}' failed: {}", method, e);
+            RpcResponse {
+                jsonrpc: "2.0",
+                id: request.id,
+                result: None,
+                error: Some(e.to_string()),
+            }
+        }
+    };
+
+    Json(response)
+}
+
+#[derive(Deserialize)]
+struct PingParams {
+    #[serde(default = "default_timeout")]
+    timeout: u64,
+}
+
+fn default_timeout() -> u64 {
+    5
+}
+
+async fn dispatch_ping(client: &RpcClient, params: Value) -> Result<crate::ExecutorResult> {
+    let params: PingParams = serde_json::from_value(params)?;
+    // `timeout` only matters for a freshly-constructed client; the server's
+    // shared client keeps the default timeout it was built with at startup.
+    let ping_cmd = PingCommand::new(String::new(), params.timeout);
+    ping_cmd.execute_with_client(client).await
+}
+
+#[derive(Deserialize)]
+struct SimulateParams {
+    input_mint: String,
+    output_mint: String,
+    amount: String,
+    #[serde(default = "default_input_decimals")]
+    input_decimals: u8,
+    slippage_bps: u16,
+    route_info: Option<String>,
+    #[serde(default = "default_route_provider")]
+    route_provider: String,
+    #[serde(default)]
+    banks: bool,
+}
+
+fn default_input_decimals() -> u8 {
+    9
+}
+
+fn default_route_provider() -> String {
+    "auto".to_string()
+}
+
+async fn dispatch_simulate(client: &RpcClient, params: Value) -> Result<crate::ExecutorResult> {
+    let params: SimulateParams = serde_json::from_value(params)?;
+    let simulate_cmd = SimulateCommand::new(
+        params.input_mint,
+        params.output_mint,
+        params.amount,
+        params.input_decimals,
+        params.slippage_bps,
+        String::new(),
+        params.route_info,
+        params.route_provider,
+        params.banks,
+    );
+    simulate_cmd.execute_with_client(client).await
+}
+
+#[derive(Deserialize)]
+struct SwapParams {
+    input_mint: String,
+    output_mint: String,
+    amount: String,
+    #[serde(default = "default_input_decimals")]
+    input_decimals: u8,
+    #[serde(default = "default_swap_mode")]
+    swap_mode: String,
+    slippage_bps: u16,
+    wallet: String,
+    #[serde(default = "default_mode")]
+    mode: String,
+    idempotency_key: Option<String>,
+    route_info: Option<String>,
+    #[serde(default = "default_route_provider")]
+    route_provider: String,
+    priority_fee: Option<u64>,
+    compute_unit_limit: Option<u32>,
+    #[serde(default = "default_fee_multiplier")]
+    fee_multiplier: f64,
+    #[serde(default = "default_min_priority_fee")]
+    min_priority_fee: u64,
+    #[serde(default = "default_max_priority_fee")]
+    max_priority_fee: u64,
+    #[serde(default = "default_jito_tip_lamports")]
+    jito_tip_lamports: u64,
+    #[serde(default = "default_jito_block_engine_url")]
+    jito_block_engine_url: String,
+}
+
+fn default_swap_mode() -> String {
+    "exact-in".to_string()
+}
+
+fn default_mode() -> String {
+    "simple".to_string()
+}
+
+fn default_fee_multiplier() -> f64 {
+    1.0
+}
+
+fn default_min_priority_fee() -> u64 {
+    1
+}
+
+fn default_max_priority_fee() -> u64 {
+    1_000_000
+}
+
+fn default_jito_tip_lamports() -> u64 {
+    10_000
+}
+
+fn default_jito_block_engine_url() -> String {
+    "https://mainnet.block-engine.jito.wtf/api/v1/bundles".to_string()
+}
+
+async fn dispatch_swap(
+    client: &RpcClient,
+    idempotency_dir: &str,
+    params: Value,
+) -> Result<crate::ExecutorResult> {
+    let params: SwapParams = serde_json::from_value(params)?;
+    let swap_mode: JupiterSwapMode = params.swap_mode.parse()?;
+    let swap_cmd = SwapCommand::new(
+        params.input_mint,
+        params.output_mint,
+        params.amount,
+        params.input_decimals,
+        swap_mode,
+        params.slippage_bps,
+        params.wallet,
+        String::new(),
+        params.mode,
+        params.idempotency_key,
+        params.route_info,
+        params.route_provider,
+        params.priority_fee,
+        params.compute_unit_limit,
+        params.fee_multiplier,
+        params.min_priority_fee,
+        params.max_priority_fee,
+        params.jito_tip_lamports,
+        params.jito_block_engine_url,
+        idempotency_dir.to_string(),
+    );
+    swap_cmd.execute_with_client(client).await
+}
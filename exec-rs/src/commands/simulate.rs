@@ -1,61 +1,106 @@
-use crate::{ExecutorResult, jupiter::JupiterClient};
+use crate::{
+    ExecutorResult,
+    commands::swap::{prepend_compute_budget_instructions, writable_static_accounts},
+    fee_oracle,
+    jupiter::JupiterClient,
+    providers::{SanctumClient, SwapProvider},
+    utils,
+};
 use anyhow::Result;
 use log::{info, error, warn};
+use solana_account_decoder::{UiAccountData, UiAccountEncoding};
 use solana_client::rpc_client::RpcClient;
+use solana_program_test::ProgramTest;
 use solana_sdk::{
+    account::Account,
     commitment_config::CommitmentConfig,
     compute_budget::ComputeBudgetInstruction,
-    message::Message,
+    hash::Hash,
+    message::VersionedMessage,
     pubkey::Pubkey,
     signature::Signature,
-    transaction::Transaction,
+    transaction::VersionedTransaction,
 };
+use spl_token::solana_program::program_pack::Pack;
 use std::str::FromStr;
 
 pub struct SimulateCommand {
     input_mint: String,
     output_mint: String,
     amount: String,
+    input_decimals: u8,
     slippage_bps: u16,
     rpc_url: String,
     route_info: Option<String>,
+    route_provider: String,
+    banks: bool,
 }
 
 impl SimulateCommand {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         input_mint: String,
         output_mint: String,
         amount: String,
+        input_decimals: u8,
         slippage_bps: u16,
         rpc_url: String,
         route_info: Option<String>,
+        route_provider: String,
+        banks: bool,
     ) -> Self {
         Self {
             input_mint,
             output_mint,
             amount,
+            input_decimals,
             slippage_bps,
             rpc_url,
             route_info,
+            route_provider,
+            banks,
         }
     }
+
+    /// Whether simulation should run offline against an in-process
+    /// `solana-program-test` `BanksClient` instead of the live RPC, either
+    /// because `--banks` was passed or the `SIMULATE_BANKS` env var is set
+    /// to a truthy value - mirroring `JupiterClient`'s `MOCK_JUPITER` switch,
+    /// so the two can be combined for fully offline, deterministic runs.
+    fn use_banks_client(&self) -> bool {
+        self.banks
+            || matches!(
+                std::env::var("SIMULATE_BANKS").as_deref(),
+                Ok("1") | Ok("true") | Ok("TRUE")
+            )
+    }
     
     pub async fn execute(&self) -> Result<ExecutorResult> {
+        let client = RpcClient::new_with_commitment(
+            self.rpc_url.clone(),
+            CommitmentConfig::confirmed(),
+        );
+
+        self.execute_with_client(&client).await
+    }
+
+    /// Runs the simulation against an already-constructed client, so callers
+    /// that hold a long-lived `RpcClient` (e.g. the `Serve` command) can
+    /// reuse it across requests instead of paying per-call client setup.
+    pub async fn execute_with_client(&self, client: &RpcClient) -> Result<ExecutorResult> {
         info!("Executing simulate command");
         info!("Input mint: {}", self.input_mint);
         info!("Output mint: {}", self.output_mint);
         info!("Amount: {}", self.amount);
         info!("Slippage: {} bps", self.slippage_bps);
-        
-        let client = RpcClient::new_with_commitment(
-            self.rpc_url.clone(),
-            CommitmentConfig::confirmed(),
-        );
-        
-        match self.simulate_swap(&client).await {
-            Ok((expected_out, compute_units, logs)) => {
-                info!("Simulation successful - Expected output: {}", expected_out);
-                
+
+        match self.simulate_swap(client).await {
+            Ok((expected_out, simulated_out, price_impact, compute_units, priority_fee, logs)) => {
+                info!(
+                    "Simulation successful - Expected output: {}, simulated output: {:?}",
+                    expected_out, simulated_out
+                );
+
                 Ok(ExecutorResult {
                     success: true,
                     signature: None,
@@ -64,13 +109,17 @@ impl SimulateCommand {
                     error: None,
                     logs: Some(logs),
                     expected_out: Some(expected_out),
+                    simulated_out,
+                    realized_price_impact_pct: price_impact,
                     compute_units_used: Some(compute_units),
                     idempotency_key: None,
+                    priority_fee_micro_lamports: Some(priority_fee),
+                    max_input_amount: None,
                 })
             }
             Err(e) => {
                 error!("Simulation failed: {}", e);
-                
+
                 Ok(ExecutorResult {
                     success: false,
                     signature: None,
@@ -79,52 +128,50 @@ impl SimulateCommand {
                     error: Some(format!("Simulation failed: {}", e)),
                     logs: Some(vec![format!("Error: {}", e)]),
                     expected_out: None,
+                    simulated_out: None,
+                    realized_price_impact_pct: None,
                     compute_units_used: None,
                     idempotency_key: None,
+                    priority_fee_micro_lamports: None,
+                    max_input_amount: None,
                 })
             }
         }
     }
-    
-    async fn simulate_swap(&self, client: &RpcClient) -> Result<(String, u32, Vec<String>)> {
-        // Create Jupiter client
-        let jupiter = JupiterClient::new();
-        
-        // Get quote if route info not provided
-        let route_info = if let Some(ref info) = self.route_info {
-            serde_json::from_str(info)?
-        } else {
-            jupiter.get_quote(
-                &self.input_mint,
-                &self.output_mint,
-                &self.amount,
-                self.slippage_bps,
-            ).await?
-        };
-        
-        // Get swap transaction
-        let swap_response = jupiter.get_swap_transaction(&route_info).await?;
-        
-        // Parse the transaction
-        let transaction_bytes = base64::engine::general_purpose::STANDARD.decode(&swap_response.swap_transaction)?;
-        let transaction: Transaction = bincode::deserialize(&transaction_bytes)?;
-        
-        // Add compute budget instructions for better simulation
-        let mut instructions = transaction.message.instructions.clone();
-        
-        // Add compute unit limit instruction
-        let compute_limit_ix = ComputeBudgetInstruction::set_compute_unit_limit(400_000);
-        instructions.insert(0, compute_limit_ix);
-        
-        // Add priority fee instruction (for simulation only)
-        let priority_fee_ix = ComputeBudgetInstruction::set_compute_unit_price(1000); // 1000 microlamports
-        instructions.insert(1, priority_fee_ix);
-        
-        // Create new message with compute budget instructions
-        let message = Message::new(&instructions, Some(&transaction.message.account_keys[0]));
-        let simulation_tx = Transaction::new_unsigned(message);
-        
-        // Simulate the transaction
+
+    /// Prepends compute-budget instructions for the given compute unit limit
+    /// and price, runs the simulation once against the live RPC, and returns
+    /// `(logs, compute_units_used, post_token_balance)`.
+    /// When `output_token_account` is set, the RPC is asked to return that
+    /// account's post-simulation state so callers can read back the real
+    /// token balance the route would produce, rather than trusting the
+    /// quote's `outAmount`.
+    fn simulate_once(
+        &self,
+        client: &RpcClient,
+        message: &VersionedMessage,
+        compute_unit_limit: u32,
+        compute_unit_price: u64,
+        output_token_account: Option<&Pubkey>,
+    ) -> Result<(Vec<String>, u32, Option<u64>)> {
+        let mut message = message.clone();
+        prepend_compute_budget_instructions(
+            &mut message,
+            &[
+                ComputeBudgetInstruction::set_compute_unit_limit(compute_unit_limit),
+                ComputeBudgetInstruction::set_compute_unit_price(compute_unit_price),
+            ],
+        );
+
+        let simulation_tx = unsigned_versioned_transaction(message);
+
+        let accounts_config = output_token_account.map(|account| {
+            solana_client::rpc_config::RpcSimulateTransactionAccountsConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                addresses: vec![account.to_string()],
+            }
+        });
+
         let simulation_result = client.simulate_transaction_with_config(
             &simulation_tx,
             solana_client::rpc_config::RpcSimulateTransactionConfig {
@@ -132,28 +179,30 @@ impl SimulateCommand {
                 replace_recent_blockhash: true,
                 commitment: Some(CommitmentConfig::confirmed()),
                 encoding: None,
-                accounts: None,
+                accounts: accounts_config,
                 min_context_slot: None,
-                inner_instructions: false,
+                inner_instructions: output_token_account.is_some(),
             },
         )?;
-        
+
         let mut logs = Vec::new();
         let mut compute_units_used = 0;
-        
+        let mut post_token_balance = None;
+
         if let Some(ref sim_result) = simulation_result.value {
             if let Some(ref err) = sim_result.err {
                 return Err(anyhow::anyhow!("Simulation error: {:?}", err));
             }
-            
+
             if let Some(ref sim_logs) = sim_result.logs {
                 logs.extend(sim_logs.clone());
-                
-                // Extract compute units used from logs
+
                 for log in sim_logs {
                     if log.contains("consumed") && log.contains("compute units") {
-                        if let Some(units_str) = log.split_whitespace()
-                            .find(|s| s.parse::<u32>().is_ok()) {
+                        if let Some(units_str) = log
+                            .split_whitespace()
+                            .find(|s| s.parse::<u32>().is_ok())
+                        {
                             if let Ok(units) = units_str.parse::<u32>() {
                                 compute_units_used = units;
                             }
@@ -161,22 +210,320 @@ impl SimulateCommand {
                     }
                 }
             }
-            
+
             if let Some(ref accounts) = sim_result.accounts {
                 logs.push(format!("Accounts affected: {}", accounts.len()));
+
+                if let Some(Some(ui_account)) = accounts.first() {
+                    if let UiAccountData::Binary(data_b64, UiAccountEncoding::Base64) =
+                        &ui_account.data
+                    {
+                        let raw = base64::engine::general_purpose::STANDARD.decode(data_b64)?;
+                        if let Ok(token_account) = spl_token::state::Account::unpack(&raw) {
+                            post_token_balance = Some(token_account.amount);
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok((logs, compute_units_used, post_token_balance))
+    }
+
+    /// Dispatches a single simulation pass to either the live RPC or the
+    /// offline `BanksClient`, depending on [`use_banks_client`].
+    ///
+    /// [`use_banks_client`]: Self::use_banks_client
+    async fn simulate_once_dispatch(
+        &self,
+        client: &RpcClient,
+        message: &VersionedMessage,
+        compute_unit_limit: u32,
+        compute_unit_price: u64,
+        output_token_account: Option<&Pubkey>,
+    ) -> Result<(Vec<String>, u32, Option<u64>)> {
+        if self.use_banks_client() {
+            self.simulate_once_banks(
+                message,
+                compute_unit_limit,
+                compute_unit_price,
+                output_token_account,
+            )
+            .await
+        } else {
+            self.simulate_once(
+                client,
+                message,
+                compute_unit_limit,
+                compute_unit_price,
+                output_token_account,
+            )
+        }
+    }
+
+    /// Runs one simulation pass against an in-process
+    /// `solana-program-test` `BanksClient` instead of a live RPC, for
+    /// deterministic, network-free runs of the exact same
+    /// transaction-assembly path (compute-budget injection, signing,
+    /// lookup tables). Seeds a fresh test validator with just enough
+    /// state for this to succeed without a live cluster: the fee payer
+    /// funded with lamports, and - when asked to read one back - the
+    /// output token account pre-created so its balance can be inspected
+    /// afterwards. This is most useful paired with `MockJupiterBackend`'s
+    /// canned transaction (`MOCK_JUPITER=1`), giving a fully offline,
+    /// deterministic CI check of the assembly path end to end.
+    async fn simulate_once_banks(
+        &self,
+        message: &VersionedMessage,
+        compute_unit_limit: u32,
+        compute_unit_price: u64,
+        output_token_account: Option<&Pubkey>,
+    ) -> Result<(Vec<String>, u32, Option<u64>)> {
+        let payer = message.static_account_keys()[0];
+
+        let mut program_test = ProgramTest::default();
+        program_test.add_account(
+            payer,
+            Account {
+                lamports: 10_000_000_000,
+                ..Account::default()
+            },
+        );
+        if let Some(account) = output_token_account {
+            program_test.add_account(
+                *account,
+                Account {
+                    lamports: 1,
+                    owner: spl_token::id(),
+                    data: vec![0u8; spl_token::state::Account::LEN],
+                    ..Account::default()
+                },
+            );
+        }
+
+        let (banks_client, _payer, recent_blockhash) = program_test.start().await;
+
+        let mut message = message.clone();
+        prepend_compute_budget_instructions(
+            &mut message,
+            &[
+                ComputeBudgetInstruction::set_compute_unit_limit(compute_unit_limit),
+                ComputeBudgetInstruction::set_compute_unit_price(compute_unit_price),
+            ],
+        );
+        set_recent_blockhash(&mut message, recent_blockhash);
+
+        let banks_transaction = unsigned_versioned_transaction(message);
+
+        let simulation = banks_client
+            .simulate_transaction(banks_transaction)
+            .await?;
+
+        let mut logs = Vec::new();
+        let mut compute_units_used = 0;
+        let mut post_token_balance = None;
+
+        if let Some(ref details) = simulation.simulation_details {
+            logs.extend(details.logs.clone());
+            compute_units_used = details.units_consumed as u32;
+        }
+        if let Some(Err(err)) = simulation.result {
+            return Err(anyhow::anyhow!("Banks simulation error: {:?}", err));
+        }
+
+        if let Some(account) = output_token_account {
+            if let Some(account_data) = banks_client.get_account(*account).await? {
+                if let Ok(token_account) = spl_token::state::Account::unpack(&account_data.data) {
+                    post_token_balance = Some(token_account.amount);
+                }
             }
         }
+
+        Ok((logs, compute_units_used, post_token_balance))
+    }
+
+    #[allow(clippy::type_complexity)]
+    async fn simulate_swap(
+        &self,
+        client: &RpcClient,
+    ) -> Result<(String, Option<String>, Option<f64>, u32, u64, Vec<String>)> {
+        // Create clients for every swap provider we aggregate over
+        let jupiter = JupiterClient::new();
+        let sanctum = SanctumClient::new();
+        let providers: Vec<&dyn SwapProvider> = vec![&jupiter, &sanctum];
+
+        // Get quote if route info not provided, honoring `--route-provider`:
+        // an explicit provider name pins the quote to it, while "auto" (the
+        // default) keeps whichever provider quotes the highest outAmount
+        let (route_info, provider) = if let Some(ref info) = self.route_info {
+            // An explicitly supplied `--route-info` still needs to go to the
+            // provider named by `--route-provider`, since the two aggregators'
+            // `/swap` endpoints expect differently-shaped quote responses
+            let provider = match self.route_provider.as_str() {
+                "sanctum" => &providers[1],
+                other => {
+                    if other != "jupiter" && other != "auto" {
+                        warn!("Unknown route provider '{}', defaulting to jupiter for supplied --route-info", other);
+                    }
+                    &providers[0]
+                }
+            };
+            (serde_json::from_str(info)?, provider)
+        } else {
+            let base_amount =
+                utils::parse_amount_with_decimals(&self.amount, self.input_decimals)?.to_string();
+            match self.route_provider.as_str() {
+                "jupiter" => {
+                    let quote = providers[0]
+                        .get_quote(&self.input_mint, &self.output_mint, &base_amount, self.slippage_bps, crate::jupiter::JupiterSwapMode::ExactIn)
+                        .await?;
+                    (quote, &providers[0])
+                }
+                "sanctum" => {
+                    let quote = providers[1]
+                        .get_quote(&self.input_mint, &self.output_mint, &base_amount, self.slippage_bps, crate::jupiter::JupiterSwapMode::ExactIn)
+                        .await?;
+                    (quote, &providers[1])
+                }
+                other => {
+                    if other != "auto" {
+                        warn!("Unknown route provider '{}', falling back to auto", other);
+                    }
+                    let (index, quote) = crate::providers::best_quote(
+                        &providers,
+                        &self.input_mint,
+                        &self.output_mint,
+                        &base_amount,
+                        self.slippage_bps,
+                        crate::jupiter::JupiterSwapMode::ExactIn,
+                    ).await?;
+                    (quote, &providers[index])
+                }
+            }
+        };
+        info!("Using provider: {}", provider.name());
+
+        // Get swap transaction
+        let swap_response = provider
+            .get_swap_transaction(&route_info, "11111111111111111111111111111112")
+            .await?;
         
+        // Parse the transaction. Jupiter v6 routes that cross many hops
+        // return a `v0` message referencing address lookup tables rather
+        // than a legacy transaction, so we deserialize straight into
+        // `VersionedTransaction` - its `Deserialize` impl transparently
+        // handles both the legacy and `v0` wire formats - and simulate
+        // against its message directly, the same as `SwapCommand` does.
+        let transaction_bytes = base64::engine::general_purpose::STANDARD.decode(&swap_response.swap_transaction)?;
+        let unsigned: VersionedTransaction = bincode::deserialize(&transaction_bytes)?;
+        let message = unsigned.message;
+
+        // Derive a priority fee from recent prioritization fees on the
+        // transaction's writable accounts, rather than a hardcoded value
+        let writable_accounts = writable_static_accounts(&message);
+
+        let priority_fee = fee_oracle::recommended_priority_fee(
+            client,
+            &writable_accounts,
+            fee_oracle::DEFAULT_PERCENTILE,
+            fee_oracle::DEFAULT_MIN_PRIORITY_FEE,
+            fee_oracle::DEFAULT_MAX_PRIORITY_FEE,
+        );
+        info!("Derived priority fee: {} microlamports", priority_fee);
+
+        if self.use_banks_client() {
+            info!("Simulating offline against an in-process BanksClient");
+        }
+
+        // First dry-run with a generous compute unit limit, just to learn
+        // how many compute units the route actually consumes
+        let (_, dry_run_units, _) = self
+            .simulate_once_dispatch(client, &message, 1_400_000, priority_fee, None)
+            .await?;
+
+        // The associated token account that would receive the swap's output,
+        // for the same placeholder signer the transaction was built for
+        let output_token_account = Pubkey::from_str(&self.output_mint)
+            .ok()
+            .map(|mint| {
+                spl_associated_token_account::get_associated_token_address(
+                    &message.static_account_keys()[0],
+                    &mint,
+                )
+            });
+
+        // Re-simulate with the compute unit limit right-sized to the
+        // measured usage plus a 10% safety margin, this time also reading
+        // back the output token account's post-simulation balance
+        let compute_unit_limit = (dry_run_units as f64 * 1.1).ceil() as u32;
+        let (mut logs, compute_units_used, simulated_amount) = self
+            .simulate_once_dispatch(
+                client,
+                &message,
+                compute_unit_limit,
+                priority_fee,
+                output_token_account.as_ref(),
+            )
+            .await?;
+
         // Extract expected output from route info
         let expected_out = if let Some(out_amount) = route_info.get("outAmount") {
             out_amount.as_str().unwrap_or("0").to_string()
         } else {
             "0".to_string()
         };
-        
-        logs.push(format!("Expected output: {}", expected_out));
+
+        let simulated_out = simulated_amount.map(|amount| amount.to_string());
+        let price_impact = match (expected_out.parse::<f64>(), simulated_amount) {
+            (Ok(quoted), Some(simulated)) if quoted > 0.0 => {
+                Some(((quoted - simulated as f64) / quoted) * 100.0)
+            }
+            _ => None,
+        };
+
+        logs.push(format!("Expected output (quoted): {}", expected_out));
+        if let Some(ref simulated) = simulated_out {
+            logs.push(format!("Simulated output (realized): {}", simulated));
+        }
+        if let Some(impact) = price_impact {
+            logs.push(format!("Realized price impact vs quote: {:.4}%", impact));
+        }
         logs.push(format!("Compute units used: {}", compute_units_used));
-        
-        Ok((expected_out, compute_units_used, logs))
+        logs.push(format!("Priority fee: {} microlamports", priority_fee));
+        if self.use_banks_client() {
+            logs.push("Backend: in-process BanksClient (offline)".to_string());
+        }
+
+        Ok((
+            expected_out,
+            simulated_out,
+            price_impact,
+            compute_units_used,
+            priority_fee,
+            logs,
+        ))
+    }
+}
+
+/// Builds a `VersionedTransaction` with placeholder signatures for
+/// simulation-only use, never verified since every call site here passes
+/// `sig_verify: false` (or BanksClient, which doesn't check at all). There's
+/// no wallet to sign with in `SimulateCommand` - the provider quoted against
+/// a placeholder pubkey - so we fill in the number of signatures the
+/// message declares rather than actually signing.
+fn unsigned_versioned_transaction(message: VersionedMessage) -> VersionedTransaction {
+    let num_signatures = message.header().num_required_signatures as usize;
+    VersionedTransaction {
+        signatures: vec![Signature::default(); num_signatures],
+        message,
+    }
+}
+
+/// Sets a `VersionedMessage`'s recent blockhash in place, for both the
+/// legacy and `v0` variants.
+fn set_recent_blockhash(message: &mut VersionedMessage, blockhash: Hash) {
+    match message {
+        VersionedMessage::Legacy(m) => m.recent_blockhash = blockhash,
+        VersionedMessage::V0(m) => m.recent_blockhash = blockhash,
     }
 }
@@ -1,103 +1,224 @@
-use crate::{ExecutorResult, jupiter::JupiterClient, utils};
+use crate::{
+    ExecutorResult,
+    fee_oracle,
+    idempotency::IdempotencyStore,
+    jupiter::{JupiterClient, JupiterSwapMode},
+    providers::{SanctumClient, SwapProvider},
+    utils,
+};
 use anyhow::Result;
 use log::{info, error, warn};
+use reqwest::Client;
+use serde_json::json;
 use solana_client::rpc_client::RpcClient;
 use solana_sdk::{
     commitment_config::CommitmentConfig,
     compute_budget::ComputeBudgetInstruction,
-    message::Message,
+    instruction::{CompiledInstruction, Instruction},
+    message::{Message, VersionedMessage},
     pubkey::Pubkey,
     signature::{Keypair, Signature},
     signer::Signer,
-    transaction::Transaction,
+    system_instruction,
+    transaction::{Transaction, VersionedTransaction},
 };
 use std::str::FromStr;
 use std::time::{Duration, Instant};
 
+/// Known Jito block-engine tip accounts (mainnet). Any one of these may be
+/// used as the destination of the bundle's tip transfer; we pick one by the
+/// first byte of the signer's pubkey, so tips are spread across wallets
+/// while a given wallet always tips the same account.
+const JITO_TIP_ACCOUNTS: &[&str] = &[
+    "96gYZGLnJYVFmbjzopPSU6QiEV5fGqZNyN9nmNhvrZU5",
+    "HFqU5x63VTqvQss8hp11i4wVV8bD44PvwucfZ2bU7gRe",
+    "Cw8CFyM9FkoMi7K7Crf6HNQqf4uEMzpKw6QNghXLvLkY",
+    "ADaUMid9yfUytqMBgopwjb2DTLSokTSzL1zt6iGPaS49",
+    "DfXygSm4jCyNCybVYYK6DwvWqjKee8pbDmJGcLWNDXjh",
+    "ADuUkR4vqLUMWXxW9gh6D6L8pMSawimctcNZ5pGwDcEt",
+    "DttWaMuVvTiduZRnguLF7jNxTgiMBZ1hyAumKUiL2KRL",
+    "3AVi9Tg9Uo68tJfuvoKvqKNWKkC5wPdSSdeBnizKZ6jT",
+];
+
 pub struct SwapCommand {
     input_mint: String,
     output_mint: String,
     amount: String,
+    input_decimals: u8,
+    swap_mode: JupiterSwapMode,
     slippage_bps: u16,
     wallet: String,
     rpc_url: String,
     mode: String,
     idempotency_key: Option<String>,
     route_info: Option<String>,
+    route_provider: String,
     priority_fee: Option<u64>,
     compute_unit_limit: Option<u32>,
+    fee_multiplier: f64,
+    min_priority_fee: u64,
+    max_priority_fee: u64,
+    jito_tip_lamports: u64,
+    jito_block_engine_url: String,
+    idempotency_dir: String,
 }
 
 impl SwapCommand {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         input_mint: String,
         output_mint: String,
         amount: String,
+        input_decimals: u8,
+        swap_mode: JupiterSwapMode,
         slippage_bps: u16,
         wallet: String,
         rpc_url: String,
         mode: String,
         idempotency_key: Option<String>,
         route_info: Option<String>,
+        route_provider: String,
         priority_fee: Option<u64>,
         compute_unit_limit: Option<u32>,
+        fee_multiplier: f64,
+        min_priority_fee: u64,
+        max_priority_fee: u64,
+        jito_tip_lamports: u64,
+        jito_block_engine_url: String,
+        idempotency_dir: String,
     ) -> Self {
         Self {
             input_mint,
             output_mint,
             amount,
+            input_decimals,
+            swap_mode,
             slippage_bps,
             wallet,
             rpc_url,
             mode,
             idempotency_key,
             route_info,
+            route_provider,
             priority_fee,
             compute_unit_limit,
+            fee_multiplier,
+            min_priority_fee,
+            max_priority_fee,
+            jito_tip_lamports,
+            jito_block_engine_url,
+            idempotency_dir,
         }
     }
     
     pub async fn execute(&self) -> Result<ExecutorResult> {
+        let client = RpcClient::new_with_commitment(
+            self.rpc_url.clone(),
+            CommitmentConfig::confirmed(),
+        );
+
+        self.execute_with_client(&client).await
+    }
+
+    /// Runs the swap against an already-constructed client, so callers that
+    /// hold a long-lived `RpcClient` (e.g. the `Serve` command) can reuse it
+    /// across requests instead of paying per-call client setup.
+    pub async fn execute_with_client(&self, client: &RpcClient) -> Result<ExecutorResult> {
         info!("Executing swap command");
         info!("Input mint: {}", self.input_mint);
         info!("Output mint: {}", self.output_mint);
         info!("Amount: {}", self.amount);
         info!("Slippage: {} bps", self.slippage_bps);
         info!("Mode: {}", self.mode);
-        
+
         if let Some(ref key) = self.idempotency_key {
             info!("Idempotency key: {}", key);
+            return self.execute_with_idempotency(client, key).await;
         }
-        
-        let client = RpcClient::new_with_commitment(
-            self.rpc_url.clone(),
-            CommitmentConfig::confirmed(),
-        );
-        
-        match self.execute_swap(&client).await {
-            Ok((signature, received_amount, slot)) => {
+
+        Ok(self.run_swap_to_result(client).await)
+    }
+
+    /// Guards `execute_swap` behind the on-disk idempotency store: a repeat
+    /// call with the same `key` that already completed returns the stored
+    /// result without resubmitting, and a call that's already in flight for
+    /// `key` is rejected rather than racing a second submission. This is
+    /// what turns a retry storm from the calling layer into a no-op after
+    /// the first attempt lands.
+    async fn execute_with_idempotency(&self, client: &RpcClient, key: &str) -> Result<ExecutorResult> {
+        let store = IdempotencyStore::new(&self.idempotency_dir)?;
+
+        if let Some(cached) = store.get(key)? {
+            info!("Idempotency key '{}' already completed, returning stored result", key);
+            return Ok(cached);
+        }
+
+        let guard = match store.begin(key) {
+            Ok(guard) => guard,
+            Err(e) => {
+                warn!("Swap rejected: {}", e);
+                return Ok(ExecutorResult {
+                    success: false,
+                    signature: None,
+                    received_amount: None,
+                    slot: None,
+                    error: Some(e.to_string()),
+                    logs: Some(vec![format!("Error: {}", e)]),
+                    expected_out: None,
+                    simulated_out: None,
+                    realized_price_impact_pct: None,
+                    compute_units_used: None,
+                    idempotency_key: self.idempotency_key.clone(),
+                    priority_fee_micro_lamports: None,
+                    max_input_amount: None,
+                });
+            }
+        };
+
+        let result = self.run_swap_to_result(client).await;
+        guard.commit(&result)?;
+        Ok(result)
+    }
+
+    /// Runs `execute_swap` and folds its outcome into an `ExecutorResult`,
+    /// never itself failing - errors become `success: false` results so
+    /// callers (the CLI, `Serve`, and the idempotency store) always have a
+    /// result to report or persist.
+    async fn run_swap_to_result(&self, client: &RpcClient) -> ExecutorResult {
+        match self.execute_swap(client).await {
+            Ok((signature, received_amount, slot, max_input_amount, priority_fee)) => {
                 info!("Swap successful - Signature: {}", signature);
-                
-                Ok(ExecutorResult {
+
+                let mut logs = vec![
+                    format!("Transaction signature: {}", signature),
+                    format!("Received amount: {}", received_amount),
+                    format!("Confirmed at slot: {}", slot),
+                    format!("Priority fee: {} microlamports", priority_fee),
+                ];
+                if let Some(ref max_input) = max_input_amount {
+                    logs.push(format!("Max input spent (ExactOut): {}", max_input));
+                }
+
+                ExecutorResult {
                     success: true,
                     signature: Some(signature),
                     received_amount: Some(received_amount),
                     slot: Some(slot),
                     error: None,
-                    logs: Some(vec![
-                        format!("Transaction signature: {}", signature),
-                        format!("Received amount: {}", received_amount),
-                        format!("Confirmed at slot: {}", slot),
-                    ]),
+                    logs: Some(logs),
                     expected_out: None,
+                    simulated_out: None,
+                    realized_price_impact_pct: None,
                     compute_units_used: None,
                     idempotency_key: self.idempotency_key.clone(),
-                })
+                    priority_fee_micro_lamports: Some(priority_fee),
+                    max_input_amount,
+                }
             }
             Err(e) => {
                 error!("Swap failed: {}", e);
-                
-                Ok(ExecutorResult {
+
+                ExecutorResult {
                     success: false,
                     signature: None,
                     received_amount: None,
@@ -105,121 +226,218 @@ impl SwapCommand {
                     error: Some(format!("Swap failed: {}", e)),
                     logs: Some(vec![format!("Error: {}", e)]),
                     expected_out: None,
+                    simulated_out: None,
+                    realized_price_impact_pct: None,
                     compute_units_used: None,
                     idempotency_key: self.idempotency_key.clone(),
-                })
+                    priority_fee_micro_lamports: None,
+                    max_input_amount: None,
+                }
             }
         }
     }
-    
-    async fn execute_swap(&self, client: &RpcClient) -> Result<(String, String, u64)> {
+
+    async fn execute_swap(&self, client: &RpcClient) -> Result<(String, String, u64, Option<String>, u64)> {
         // Load wallet
         let wallet_keypair = utils::load_wallet(&self.wallet)?;
         info!("Loaded wallet: {}", wallet_keypair.pubkey());
-        
-        // Create Jupiter client
+
+        // Create a client for every swap provider we can route through
         let jupiter = JupiterClient::new();
-        
-        // Get quote if route info not provided
-        let route_info = if let Some(ref info) = self.route_info {
-            serde_json::from_str(info)?
+        let sanctum = SanctumClient::new();
+        let providers: Vec<&dyn SwapProvider> = vec![&jupiter, &sanctum];
+
+        // Get quote if route info not provided, honoring `--route-provider`:
+        // an explicit provider name pins the quote to it, while "auto" (the
+        // default) keeps whichever provider quotes the highest outAmount
+        let (route_info, provider) = if let Some(ref info) = self.route_info {
+            // An explicitly supplied `--route-info` still needs to go to the
+            // provider named by `--route-provider`, since the two aggregators'
+            // `/swap` endpoints expect differently-shaped quote responses
+            let provider = match self.route_provider.as_str() {
+                "sanctum" => providers[1],
+                other => {
+                    if other != "jupiter" && other != "auto" {
+                        warn!("Unknown route provider '{}', defaulting to jupiter for supplied --route-info", other);
+                    }
+                    providers[0]
+                }
+            };
+            (serde_json::from_str(info)?, provider)
         } else {
-            jupiter.get_quote(
-                &self.input_mint,
-                &self.output_mint,
-                &self.amount,
-                self.slippage_bps,
-            ).await?
+            let base_amount =
+                utils::parse_amount_with_decimals(&self.amount, self.input_decimals)?.to_string();
+            match self.route_provider.as_str() {
+                "jupiter" => {
+                    let quote = providers[0]
+                        .get_quote(&self.input_mint, &self.output_mint, &base_amount, self.slippage_bps, self.swap_mode)
+                        .await?;
+                    (quote, providers[0])
+                }
+                "sanctum" => {
+                    let quote = providers[1]
+                        .get_quote(&self.input_mint, &self.output_mint, &base_amount, self.slippage_bps, self.swap_mode)
+                        .await?;
+                    (quote, providers[1])
+                }
+                other => {
+                    if other != "auto" {
+                        warn!("Unknown route provider '{}', falling back to auto", other);
+                    }
+                    let (index, quote) = crate::providers::best_quote(
+                        &providers,
+                        &self.input_mint,
+                        &self.output_mint,
+                        &base_amount,
+                        self.slippage_bps,
+                        self.swap_mode,
+                    ).await?;
+                    (quote, providers[index])
+                }
+            }
         };
-        
+        info!("Using provider: {}", provider.name());
+
+        // In ExactOut mode, `amount` was the desired output; the quote's
+        // `otherAmountThreshold` is the maximum input that would be spent
+        let max_input_amount = if self.swap_mode == JupiterSwapMode::ExactOut {
+            route_info
+                .get("otherAmountThreshold")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string())
+        } else {
+            None
+        };
+        if let Some(ref max_input) = max_input_amount {
+            info!("Max input to be spent (ExactOut): {}", max_input);
+        }
+
         // Get swap transaction
-        let swap_response = jupiter.get_swap_transaction(&route_info).await?;
-        
+        let swap_response = provider
+            .get_swap_transaction(&route_info, &wallet_keypair.pubkey().to_string())
+            .await?;
+
         // Parse and prepare transaction
-        let mut transaction = self.prepare_transaction(&swap_response.swap_transaction, &wallet_keypair)?;
-        
-        // Add compute budget instructions if specified
-        if self.priority_fee.is_some() || self.compute_unit_limit.is_some() {
-            transaction = self.add_compute_budget_instructions(transaction, &wallet_keypair)?;
-        }
-        
+        let transaction = self.prepare_transaction(&swap_response.swap_transaction, &wallet_keypair)?;
+
+        // Always attach compute budget instructions, deriving a priority
+        // fee from recent network conditions unless the caller pinned one
+        let (transaction, priority_fee) =
+            self.add_compute_budget_instructions(client, transaction, &wallet_keypair)?;
+
         // Execute based on mode
-        match self.mode.as_str() {
+        let (signature, received_amount, slot) = match self.mode.as_str() {
             "simple" => self.execute_simple_swap(client, transaction).await,
-            "jito" => self.execute_jito_swap(client, transaction).await,
+            "jito" => self.execute_jito_swap(client, transaction, &wallet_keypair).await,
             "bloxroute" => self.execute_bloxroute_swap(client, transaction).await,
             _ => {
                 warn!("Unknown execution mode: {}, falling back to simple", self.mode);
                 self.execute_simple_swap(client, transaction).await
             }
-        }
+        }?;
+
+        Ok((signature, received_amount, slot, max_input_amount, priority_fee))
     }
     
-    fn prepare_transaction(&self, swap_transaction: &str, wallet: &Keypair) -> Result<Transaction> {
+    /// Decodes the (possibly versioned) base64 transaction blob returned by
+    /// a swap provider and re-signs it. Jupiter v6 routes that cross many
+    /// hops return a `v0` message referencing address lookup tables rather
+    /// than a legacy transaction, so we deserialize straight into
+    /// `VersionedTransaction` - its `Deserialize` impl transparently
+    /// handles both the legacy and `v0` wire formats - and re-derive the
+    /// signature from the message as-is, preserving any
+    /// `address_table_lookups` untouched.
+    fn prepare_transaction(&self, swap_transaction: &str, wallet: &Keypair) -> Result<VersionedTransaction> {
         // Decode base64 transaction
         let transaction_bytes = base64::engine::general_purpose::STANDARD.decode(swap_transaction)?;
-        let mut transaction: Transaction = bincode::deserialize(&transaction_bytes)?;
-        
-        // Get recent blockhash
-        let recent_blockhash = transaction.message.recent_blockhash;
-        
-        // Sign the transaction
-        transaction.sign(&[wallet], recent_blockhash);
-        
+        let unsigned: VersionedTransaction = bincode::deserialize(&transaction_bytes)?;
+
+        // Re-sign against the message as provided; `try_new` recomputes the
+        // signature list from scratch rather than patching the existing one
+        let transaction = VersionedTransaction::try_new(unsigned.message, &[wallet])?;
+
         Ok(transaction)
     }
-    
-    fn add_compute_budget_instructions(&self, mut transaction: Transaction, wallet: &Keypair) -> Result<Transaction> {
-        let mut instructions = Vec::new();
 
+    /// Attaches compute-budget instructions to `transaction`, deriving the
+    /// priority fee from `getRecentPrioritizationFees` over the
+    /// transaction's writable accounts (via [`fee_oracle`]) unless
+    /// `--priority-fee` pinned an explicit value. Returns the re-signed
+    /// transaction alongside the priority fee that was actually used, so
+    /// callers can surface it in `ExecutorResult`.
+    fn add_compute_budget_instructions(
+        &self,
+        client: &RpcClient,
+        transaction: VersionedTransaction,
+        wallet: &Keypair,
+    ) -> Result<(VersionedTransaction, u64)> {
         // Add compute unit limit if specified, otherwise use dynamic limit
         let compute_limit = self.compute_unit_limit.unwrap_or_else(|| {
             // Dynamic compute limit based on transaction complexity
             let base_limit = 200_000u32;
-            let instruction_count = transaction.message.instructions.len() as u32;
+            let instruction_count = transaction.message.instructions().len() as u32;
             let dynamic_limit = base_limit + (instruction_count * 50_000);
             std::cmp::min(dynamic_limit, 1_400_000) // Cap at 1.4M CU
         });
 
-        instructions.push(ComputeBudgetInstruction::set_compute_unit_limit(compute_limit));
-
-        // Add priority fee if specified, otherwise use dynamic fee
-        let priority_fee = self.priority_fee.unwrap_or_else(|| {
-            // Dynamic priority fee based on network congestion
-            // This is simplified - in production you'd query recent fees
-            1000u64 // 1000 microlamports default
-        });
-
-        instructions.push(ComputeBudgetInstruction::set_compute_unit_price(priority_fee));
+        // Use the pinned priority fee if given, otherwise derive one from
+        // recent prioritization fees on this transaction's writable
+        // accounts, scaled by `--fee-multiplier` and clamped to
+        // `[--min-priority-fee, --max-priority-fee]`
+        let priority_fee = match self.priority_fee {
+            Some(fee) => fee,
+            None => {
+                let writable_accounts = writable_static_accounts(&transaction.message);
+                let base_fee = fee_oracle::recommended_priority_fee(
+                    client,
+                    &writable_accounts,
+                    fee_oracle::DEFAULT_PERCENTILE,
+                    self.min_priority_fee,
+                    self.max_priority_fee,
+                );
+                let scaled = ((base_fee as f64) * self.fee_multiplier).round() as u64;
+                scaled.clamp(self.min_priority_fee, self.max_priority_fee)
+            }
+        };
 
-        // Add original instructions
-        instructions.extend(transaction.message.instructions.clone());
+        let mut message = transaction.message;
+        prepend_compute_budget_instructions(
+            &mut message,
+            &[
+                ComputeBudgetInstruction::set_compute_unit_limit(compute_limit),
+                ComputeBudgetInstruction::set_compute_unit_price(priority_fee),
+            ],
+        );
 
-        // Create new message
-        let message = Message::new(&instructions, Some(&wallet.pubkey()));
-        let new_transaction = Transaction::new(&[wallet], message, transaction.message.recent_blockhash);
+        // Re-sign now that the message (and therefore the data that gets
+        // signed) has changed
+        let new_transaction = VersionedTransaction::try_new(message, &[wallet])?;
 
         info!("Added compute budget: {} CU limit, {} microlamports priority fee",
                compute_limit, priority_fee);
 
-        Ok(new_transaction)
+        Ok((new_transaction, priority_fee))
     }
-    
-    async fn execute_simple_swap(&self, client: &RpcClient, transaction: Transaction) -> Result<(String, String, u64)> {
+
+    async fn execute_simple_swap(&self, client: &RpcClient, transaction: VersionedTransaction) -> Result<(String, String, u64)> {
         info!("Executing simple swap");
 
         // Get current block height for timeout calculation
-        let current_block_height = client.get_block_height()?;
+        let current_block_height = tokio::task::block_in_place(|| client.get_block_height())?;
         let last_valid_block_height = current_block_height + 150; // ~1 minute timeout
 
         info!("Current block height: {}, last valid: {}",
                current_block_height, last_valid_block_height);
 
         // Send transaction with timeout awareness
-        let signature = client.send_transaction(&transaction)?;
+        let signature = tokio::task::block_in_place(|| client.send_transaction(&transaction))?;
         info!("Transaction sent: {}", signature);
 
-        // Confirm transaction with block height timeout
+        // Confirm transaction with block height timeout. Every blocking RPC
+        // call and the poll delay below run through `block_in_place`/
+        // `tokio::time::sleep` rather than directly blocking this thread,
+        // since this now runs behind `Serve`'s shared Tokio runtime, where a
+        // stalled worker thread would starve every other in-flight request
         let mut attempts = 0;
         let max_attempts = 60; // 60 seconds max
 
@@ -227,7 +445,7 @@ impl SwapCommand {
             attempts += 1;
 
             // Check if we've exceeded the valid block height
-            let current_height = client.get_block_height()?;
+            let current_height = tokio::task::block_in_place(|| client.get_block_height())?;
             if current_height > last_valid_block_height {
                 return Err(anyhow::anyhow!(
                     "Transaction expired: current height {} > last valid {}",
@@ -236,7 +454,7 @@ impl SwapCommand {
             }
 
             // Check transaction status
-            match client.get_signature_status(&signature)? {
+            match tokio::task::block_in_place(|| client.get_signature_status(&signature))? {
                 Some(Ok(())) => {
                     info!("Transaction confirmed at block height: {}", current_height);
                     break;
@@ -249,20 +467,22 @@ impl SwapCommand {
                         return Err(anyhow::anyhow!("Transaction confirmation timeout"));
                     }
                     // Wait 1 second before next check
-                    std::thread::sleep(std::time::Duration::from_secs(1));
+                    tokio::time::sleep(Duration::from_secs(1)).await;
                 }
             }
         }
 
         // Get transaction details
-        let confirmed_tx = client.get_transaction_with_config(
-            &signature,
-            solana_client::rpc_config::RpcTransactionConfig {
-                encoding: Some(solana_transaction_status::UiTransactionEncoding::Json),
-                commitment: Some(CommitmentConfig::confirmed()),
-                max_supported_transaction_version: Some(0),
-            },
-        )?;
+        let confirmed_tx = tokio::task::block_in_place(|| {
+            client.get_transaction_with_config(
+                &signature,
+                solana_client::rpc_config::RpcTransactionConfig {
+                    encoding: Some(solana_transaction_status::UiTransactionEncoding::Json),
+                    commitment: Some(CommitmentConfig::confirmed()),
+                    max_supported_transaction_version: Some(0),
+                },
+            )
+        })?;
 
         let slot = confirmed_tx.slot;
 
@@ -272,13 +492,132 @@ impl SwapCommand {
         Ok((signature.to_string(), received_amount, slot))
     }
     
-    async fn execute_jito_swap(&self, _client: &RpcClient, _transaction: Transaction) -> Result<(String, String, u64)> {
-        // TODO: Implement Jito bundle execution
-        warn!("Jito execution not yet implemented, falling back to simple");
-        Err(anyhow::anyhow!("Jito execution not implemented"))
+    /// Submits `transaction` as a two-transaction Jito bundle - the swap
+    /// itself plus a tip transfer to one of the known block-engine tip
+    /// accounts - for atomic, MEV-protected landing. Polls `getBundleStatuses`
+    /// until the bundle lands or the transaction's block-height deadline
+    /// passes.
+    async fn execute_jito_swap(
+        &self,
+        client: &RpcClient,
+        transaction: VersionedTransaction,
+        wallet: &Keypair,
+    ) -> Result<(String, String, u64)> {
+        info!("Executing Jito bundle swap");
+
+        let recent_blockhash = tokio::task::block_in_place(|| client.get_latest_blockhash())?;
+        let current_block_height = tokio::task::block_in_place(|| client.get_block_height())?;
+        let last_valid_block_height = current_block_height + 150; // ~1 minute timeout
+
+        let tip_account = Pubkey::from_str(
+            JITO_TIP_ACCOUNTS[wallet.pubkey().to_bytes()[0] as usize % JITO_TIP_ACCOUNTS.len()],
+        )?;
+        let tip_instruction =
+            system_instruction::transfer(&wallet.pubkey(), &tip_account, self.jito_tip_lamports);
+        let tip_message = Message::new(&[tip_instruction], Some(&wallet.pubkey()));
+        let tip_transaction = Transaction::new(&[wallet], tip_message, recent_blockhash);
+
+        let signature = *transaction.signatures.first().ok_or_else(|| {
+            anyhow::anyhow!("swap transaction has no signatures to report")
+        })?;
+
+        let encoded_bundle: Vec<String> = vec![
+            base64::engine::general_purpose::STANDARD
+                .encode(bincode::serialize(&transaction)?),
+            base64::engine::general_purpose::STANDARD
+                .encode(bincode::serialize(&tip_transaction)?),
+        ];
+
+        info!(
+            "Submitting bundle with {} lamport tip to {}",
+            self.jito_tip_lamports, tip_account
+        );
+
+        let http = Client::new();
+        let send_response: serde_json::Value = http
+            .post(&self.jito_block_engine_url)
+            .json(&json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "sendBundle",
+                "params": [encoded_bundle, {"encoding": "base64"}],
+            }))
+            .send()
+            .await?
+            .json()
+            .await?;
+
+        if let Some(error) = send_response.get("error") {
+            return Err(anyhow::anyhow!("sendBundle failed: {}", error));
+        }
+        let bundle_id = send_response
+            .get("result")
+            .and_then(serde_json::Value::as_str)
+            .ok_or_else(|| anyhow::anyhow!("sendBundle response missing result bundle id"))?
+            .to_string();
+        info!("Bundle submitted: {}", bundle_id);
+
+        // Poll getBundleStatuses until the bundle lands or we pass the
+        // transaction's last valid block height. Uses `tokio::time::sleep`
+        // rather than `std::thread::sleep` since this now runs inside the
+        // persistent `Serve` server, where blocking a worker thread for up
+        // to a minute would cap real concurrency across requests
+        loop {
+            let current_height = tokio::task::block_in_place(|| client.get_block_height())?;
+            if current_height > last_valid_block_height {
+                return Err(anyhow::anyhow!(
+                    "Bundle expired: current height {} > last valid {}",
+                    current_height, last_valid_block_height
+                ));
+            }
+
+            let status_response: serde_json::Value = http
+                .post(&self.jito_block_engine_url)
+                .json(&json!({
+                    "jsonrpc": "2.0",
+                    "id": 1,
+                    "method": "getBundleStatuses",
+                    "params": [[bundle_id]],
+                }))
+                .send()
+                .await?
+                .json()
+                .await?;
+
+            let status = status_response
+                .get("result")
+                .and_then(|r| r.get("value"))
+                .and_then(|v| v.as_array())
+                .and_then(|statuses| statuses.first());
+
+            if let Some(status) = status {
+                if let Some(err) = status.get("err").filter(|e| !e.is_null()) {
+                    return Err(anyhow::anyhow!("Bundle failed: {:?}", err));
+                }
+                if let Some(slot) = status.get("slot").and_then(serde_json::Value::as_u64) {
+                    info!("Bundle landed at slot: {}", slot);
+
+                    let confirmed_tx = tokio::task::block_in_place(|| {
+                        client.get_transaction_with_config(
+                            &signature,
+                            solana_client::rpc_config::RpcTransactionConfig {
+                                encoding: Some(solana_transaction_status::UiTransactionEncoding::Json),
+                                commitment: Some(CommitmentConfig::confirmed()),
+                                max_supported_transaction_version: Some(0),
+                            },
+                        )
+                    })?;
+                    let received_amount = self.extract_received_amount(&confirmed_tx)?;
+
+                    return Ok((signature.to_string(), received_amount, slot));
+                }
+            }
+
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        }
     }
-    
-    async fn execute_bloxroute_swap(&self, _client: &RpcClient, _transaction: Transaction) -> Result<(String, String, u64)> {
+
+    async fn execute_bloxroute_swap(&self, _client: &RpcClient, _transaction: VersionedTransaction) -> Result<(String, String, u64)> {
         // TODO: Implement bloXroute execution
         warn!("bloXroute execution not yet implemented, falling back to simple");
         Err(anyhow::anyhow!("bloXroute execution not implemented"))
@@ -302,3 +641,158 @@ impl SwapCommand {
         Ok("0".to_string())
     }
 }
+
+/// Returns the static (non-lookup-table) account keys a `VersionedMessage`
+/// marks as writable, for feeding into `fee_oracle::recommended_priority_fee`.
+pub(crate) fn writable_static_accounts(message: &VersionedMessage) -> Vec<Pubkey> {
+    match message {
+        VersionedMessage::Legacy(m) => m
+            .account_keys
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| m.is_writable(*index))
+            .map(|(_, key)| *key)
+            .collect(),
+        VersionedMessage::V0(m) => m
+            .account_keys
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| m.is_writable(*index))
+            .map(|(_, key)| *key)
+            .collect(),
+    }
+}
+
+/// Prepends `new_instructions` to a `VersionedMessage`'s compiled
+/// instruction list, working on whichever variant (legacy or `v0`) the
+/// provider sent back. Operating on the compiled form in place - rather
+/// than decompiling into `Instruction`s and rebuilding the message - is
+/// what lets this preserve a `v0` message's `address_table_lookups`
+/// untouched.
+pub(crate) fn prepend_compute_budget_instructions(message: &mut VersionedMessage, new_instructions: &[Instruction]) {
+    match message {
+        VersionedMessage::Legacy(legacy) => {
+            prepend_compiled(&mut legacy.header, &mut legacy.account_keys, &mut legacy.instructions, new_instructions);
+        }
+        VersionedMessage::V0(v0_message) => {
+            prepend_compiled(&mut v0_message.header, &mut v0_message.account_keys, &mut v0_message.instructions, new_instructions);
+        }
+    }
+}
+
+/// Appends any program ids `new_instructions` reference that aren't
+/// already static account keys (as trailing readonly, unsigned accounts),
+/// shifts every existing compiled instruction's account/program indices
+/// that pointed past the old static-key boundary - including accounts
+/// resolved through a `v0` message's address lookup tables, whose indices
+/// are counted immediately after the static keys - and inserts the new
+/// instructions at the front.
+fn prepend_compiled(
+    header: &mut solana_sdk::message::MessageHeader,
+    account_keys: &mut Vec<Pubkey>,
+    instructions: &mut Vec<CompiledInstruction>,
+    new_instructions: &[Instruction],
+) {
+    let original_len = account_keys.len();
+    let mut added = 0u8;
+
+    let compiled: Vec<CompiledInstruction> = new_instructions
+        .iter()
+        .map(|ix| {
+            let program_id_index = match account_keys.iter().position(|key| key == &ix.program_id) {
+                Some(index) => index as u8,
+                None => {
+                    account_keys.push(ix.program_id);
+                    added += 1;
+                    (account_keys.len() - 1) as u8
+                }
+            };
+            CompiledInstruction {
+                program_id_index,
+                accounts: Vec::new(),
+                data: ix.data.clone(),
+            }
+        })
+        .collect();
+
+    if added > 0 {
+        header.num_readonly_unsigned_accounts += added;
+
+        for instruction in instructions.iter_mut() {
+            if instruction.program_id_index as usize >= original_len {
+                instruction.program_id_index += added;
+            }
+            for account_index in instruction.accounts.iter_mut() {
+                if *account_index as usize >= original_len {
+                    *account_index += added;
+                }
+            }
+        }
+    }
+
+    for (offset, ix) in compiled.into_iter().enumerate() {
+        instructions.insert(offset, ix);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::message::{Message, MessageHeader};
+
+    fn legacy_message_with_program(payer: Pubkey, program: Pubkey) -> VersionedMessage {
+        VersionedMessage::Legacy(Message {
+            header: MessageHeader {
+                num_required_signatures: 1,
+                num_readonly_signed_accounts: 0,
+                num_readonly_unsigned_accounts: 1,
+            },
+            account_keys: vec![payer, program],
+            recent_blockhash: Default::default(),
+            instructions: vec![CompiledInstruction {
+                program_id_index: 1,
+                accounts: vec![0],
+                data: vec![],
+            }],
+        })
+    }
+
+    #[test]
+    fn writable_static_accounts_excludes_readonly_program_ids() {
+        let payer = Pubkey::new_unique();
+        let program = Pubkey::new_unique();
+        let message = legacy_message_with_program(payer, program);
+
+        assert_eq!(writable_static_accounts(&message), vec![payer]);
+    }
+
+    #[test]
+    fn prepend_compute_budget_instructions_appends_program_and_keeps_lower_indices() {
+        let payer = Pubkey::new_unique();
+        let program = Pubkey::new_unique();
+        let mut message = legacy_message_with_program(payer, program);
+
+        prepend_compute_budget_instructions(
+            &mut message,
+            &[ComputeBudgetInstruction::set_compute_unit_limit(1_000_000)],
+        );
+
+        let VersionedMessage::Legacy(legacy) = &message else {
+            panic!("expected a legacy message");
+        };
+
+        // The compute budget program wasn't a static key before, so it's
+        // appended as a trailing readonly unsigned account
+        assert_eq!(legacy.account_keys.len(), 3);
+        assert_eq!(legacy.account_keys[2], solana_sdk::compute_budget::id());
+        assert_eq!(legacy.header.num_readonly_unsigned_accounts, 2);
+
+        // The new instruction is inserted at the front, referencing the
+        // newly-appended account...
+        assert_eq!(legacy.instructions[0].program_id_index, 2);
+        // ...and the original instruction's indices, which pointed at
+        // accounts before the old boundary, are left untouched
+        assert_eq!(legacy.instructions[1].program_id_index, 1);
+        assert_eq!(legacy.instructions[1].accounts, vec![0]);
+    }
+}
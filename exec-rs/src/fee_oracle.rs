@@ -0,0 +1,78 @@
+use anyhow::Result;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+
+/// Default priority-fee percentile to target when no override is given.
+pub const DEFAULT_PERCENTILE: f64 = 75.0;
+
+/// Floor so a transaction is never submitted with a zero priority fee even
+/// when recent fees were all zero.
+pub const DEFAULT_MIN_PRIORITY_FEE: u64 = 1;
+
+/// Ceiling so a noisy spike in recent fees can't make us massively overpay.
+pub const DEFAULT_MAX_PRIORITY_FEE: u64 = 1_000_000;
+
+/// Queries `getRecentPrioritizationFees` for the given writable accounts and
+/// returns the chosen percentile of the observed fees, clamped to
+/// `[min_fee, max_fee]`. Falls back to `min_fee` if the RPC call errors or
+/// returns no data, so callers always get a usable value.
+pub fn recommended_priority_fee(
+    client: &RpcClient,
+    writable_accounts: &[Pubkey],
+    percentile: f64,
+    min_fee: u64,
+    max_fee: u64,
+) -> u64 {
+    let fees = match client.get_recent_prioritization_fees(writable_accounts) {
+        Ok(fees) if !fees.is_empty() => fees,
+        Ok(_) => return min_fee,
+        Err(e) => {
+            log::warn!("getRecentPrioritizationFees failed, using floor fee: {}", e);
+            return min_fee;
+        }
+    };
+
+    let values: Vec<u64> = fees.iter().map(|f| f.prioritization_fee).collect();
+    percentile_fee(values, percentile, min_fee, max_fee)
+}
+
+/// Picks the `percentile`th value out of `values` (after sorting, the value
+/// at index `percentile / 100 * (len - 1)`, rounded to the nearest index)
+/// and clamps it to `[min_fee, max_fee]`. Split out of
+/// `recommended_priority_fee` so the percentile math can be tested without
+/// an `RpcClient` to query.
+fn percentile_fee(mut values: Vec<u64>, percentile: f64, min_fee: u64, max_fee: u64) -> u64 {
+    if values.is_empty() {
+        return min_fee;
+    }
+
+    values.sort_unstable();
+    let index = ((percentile / 100.0) * (values.len() as f64 - 1.0)).round() as usize;
+    let chosen = values[index.min(values.len() - 1)];
+
+    chosen.clamp(min_fee, max_fee)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picks_requested_percentile() {
+        let values = vec![10, 20, 30, 40, 50];
+        assert_eq!(percentile_fee(values.clone(), 0.0, 1, 1_000_000), 10);
+        assert_eq!(percentile_fee(values.clone(), 50.0, 1, 1_000_000), 30);
+        assert_eq!(percentile_fee(values, 100.0, 1, 1_000_000), 50);
+    }
+
+    #[test]
+    fn clamps_to_min_and_max() {
+        assert_eq!(percentile_fee(vec![5], 75.0, 10, 1_000_000), 10);
+        assert_eq!(percentile_fee(vec![2_000_000], 75.0, 1, 1_000_000), 1_000_000);
+    }
+
+    #[test]
+    fn falls_back_to_min_fee_when_empty() {
+        assert_eq!(percentile_fee(vec![], 75.0, 42, 1_000_000), 42);
+    }
+}
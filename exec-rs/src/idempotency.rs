@@ -0,0 +1,187 @@
+use crate::ExecutorResult;
+use anyhow::{Context, Result};
+use std::fs::{self, OpenOptions};
+use std::path::PathBuf;
+
+/// On-disk idempotency store keyed by `idempotency_key`, so a retried
+/// `swap` call with the same key can short-circuit to the stored result
+/// instead of resubmitting the trade. Each key maps to two files under
+/// `dir`: a `<key>.lock` file that exists only while the key is in
+/// flight (its atomic, create-only creation doubles as the mutual
+/// exclusion primitive that rejects concurrent duplicates), and a
+/// `<key>.json` file holding the `ExecutorResult` once the key has
+/// completed.
+pub struct IdempotencyStore {
+    dir: PathBuf,
+}
+
+impl IdempotencyStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir).with_context(|| {
+            format!("failed to create idempotency store dir: {}", dir.display())
+        })?;
+        Ok(Self { dir })
+    }
+
+    fn result_path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", key))
+    }
+
+    fn lock_path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.lock", key))
+    }
+
+    /// Returns the stored result for `key`, if a prior call already
+    /// completed under it.
+    pub fn get(&self, key: &str) -> Result<Option<ExecutorResult>> {
+        validate_key(key)?;
+
+        let path = self.result_path(key);
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let data = fs::read_to_string(&path)
+            .with_context(|| format!("failed to read idempotency result: {}", path.display()))?;
+        Ok(Some(serde_json::from_str(&data)?))
+    }
+
+    /// Claims `key` for the duration of an in-flight execution. Fails if
+    /// another call already holds the key, since the `.lock` file is
+    /// opened with `create_new` - an atomic, exclusive create that errors
+    /// if the file already exists.
+    pub fn begin(&self, key: &str) -> Result<IdempotencyGuard<'_>> {
+        validate_key(key)?;
+
+        let lock_path = self.lock_path(key);
+
+        match OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&lock_path)
+        {
+            Ok(_) => Ok(IdempotencyGuard {
+                store: self,
+                key: key.to_string(),
+                lock_path,
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => Err(anyhow::anyhow!(
+                "idempotency key '{}' is already in flight",
+                key
+            )),
+            Err(e) => {
+                Err(e).with_context(|| format!("failed to claim idempotency key: {}", key))
+            }
+        }
+    }
+}
+
+/// Holds the in-flight lock for an idempotency key. Dropping it on any
+/// return path (including an early `?`) always releases the lock, so a
+/// failed attempt doesn't permanently wedge the key; committing
+/// additionally persists the result so future calls with the same key
+/// short-circuit instead of resubmitting.
+pub struct IdempotencyGuard<'a> {
+    store: &'a IdempotencyStore,
+    key: String,
+    lock_path: PathBuf,
+}
+
+impl IdempotencyGuard<'_> {
+    pub fn commit(self, result: &ExecutorResult) -> Result<()> {
+        let data = serde_json::to_string(result)?;
+        fs::write(self.store.result_path(&self.key), data).with_context(|| {
+            format!(
+                "failed to persist idempotency result for key: {}",
+                self.key
+            )
+        })
+    }
+}
+
+impl Drop for IdempotencyGuard<'_> {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.lock_path);
+    }
+}
+
+/// Rejects anything but a short run of ASCII alphanumerics, `-`, and `_`.
+/// `idempotency_key` reaches this module as caller-controlled input (a
+/// `Serve` JSON-RPC param in particular), and both `result_path`/`lock_path`
+/// join it directly into a filename under `dir` - an allowlist here, rather
+/// than blocking `..`/`/` after the fact, is what keeps a crafted key from
+/// ever resolving outside `dir`.
+fn validate_key(key: &str) -> Result<()> {
+    let valid = !key.is_empty()
+        && key.len() <= 128
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+
+    if valid {
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!(
+            "invalid idempotency key: must be 1-128 characters of [A-Za-z0-9_-]"
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_path_traversal_keys() {
+        assert!(validate_key("../../etc/passwd").is_err());
+        assert!(validate_key("a/b").is_err());
+        assert!(validate_key("a\\b").is_err());
+        assert!(validate_key("").is_err());
+    }
+
+    #[test]
+    fn accepts_plain_keys() {
+        assert!(validate_key("retry-12345_abc").is_ok());
+    }
+
+    #[test]
+    fn roundtrips_through_begin_commit_get() {
+        let dir = std::env::temp_dir().join(format!(
+            "exec-rs-idempotency-test-{}",
+            std::process::id()
+        ));
+        let store = IdempotencyStore::new(&dir).unwrap();
+
+        assert!(store.get("order-1").unwrap().is_none());
+
+        let guard = store.begin("order-1").unwrap();
+        assert!(store.begin("order-1").is_err());
+
+        let result = ExecutorResult {
+            success: true,
+            signature: Some("sig".to_string()),
+            received_amount: None,
+            slot: None,
+            error: None,
+            logs: None,
+            expected_out: None,
+            simulated_out: None,
+            realized_price_impact_pct: None,
+            compute_units_used: None,
+            idempotency_key: Some("order-1".to_string()),
+            priority_fee_micro_lamports: None,
+            max_input_amount: None,
+        };
+        guard.commit(&result).unwrap();
+
+        let cached = store.get("order-1").unwrap().unwrap();
+        assert_eq!(cached.signature, Some("sig".to_string()));
+
+        // the lock is gone, so a second attempt no longer gets rejected as
+        // in-flight - it would see the cached result instead
+        assert!(store.begin("order-1").is_ok());
+
+        fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -1,8 +1,45 @@
 use anyhow::Result;
+use async_trait::async_trait;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use serde_json::{json, Value};
 use std::collections::HashMap;
+use std::str::FromStr;
+
+/// Jupiter v6's `swapMode` parameter. `ExactIn` quotes/swaps a fixed input
+/// amount for the best output (today's default behavior); `ExactOut`
+/// quotes/swaps for a fixed output amount, reporting the maximum input
+/// that would be spent to get it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum JupiterSwapMode {
+    #[default]
+    ExactIn,
+    ExactOut,
+}
+
+impl JupiterSwapMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JupiterSwapMode::ExactIn => "ExactIn",
+            JupiterSwapMode::ExactOut => "ExactOut",
+        }
+    }
+}
+
+impl FromStr for JupiterSwapMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().replace(['_', '-'], "").as_str() {
+            "exactin" => Ok(JupiterSwapMode::ExactIn),
+            "exactout" => Ok(JupiterSwapMode::ExactOut),
+            other => Err(anyhow::anyhow!(
+                "unknown swap mode '{}', expected ExactIn or ExactOut",
+                other
+            )),
+        }
+    }
+}
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct QuoteResponse {
@@ -76,65 +113,82 @@ pub struct SwapResponse {
     pub simulation_error: Option<Value>,
 }
 
-pub struct JupiterClient {
+/// Backend abstraction so `JupiterClient` can be pointed at the live
+/// `quote-api.jup.ag` service or at a canned, offline implementation.
+#[async_trait]
+pub trait JupiterBackend: Send + Sync {
+    async fn get_quote(
+        &self,
+        input_mint: &str,
+        output_mint: &str,
+        amount: &str,
+        slippage_bps: u16,
+        swap_mode: JupiterSwapMode,
+    ) -> Result<Value>;
+
+    async fn get_swap_transaction(
+        &self,
+        quote_response: &Value,
+        user_public_key: &str,
+    ) -> Result<SwapResponse>;
+
+    async fn health_check(&self) -> Result<bool>;
+}
+
+/// Talks to the real Jupiter v6 HTTP API.
+pub struct LiveJupiterBackend {
     client: Client,
     base_url: String,
 }
 
-impl JupiterClient {
-    pub fn new() -> Self {
-        Self {
-            client: Client::new(),
-            base_url: "https://quote-api.jup.ag/v6".to_string(),
-        }
-    }
-    
-    pub fn with_base_url(base_url: String) -> Self {
+impl LiveJupiterBackend {
+    pub fn new(base_url: String) -> Self {
         Self {
             client: Client::new(),
             base_url,
         }
     }
-    
-    pub async fn get_quote(
+}
+
+#[async_trait]
+impl JupiterBackend for LiveJupiterBackend {
+    async fn get_quote(
         &self,
         input_mint: &str,
         output_mint: &str,
         amount: &str,
         slippage_bps: u16,
+        swap_mode: JupiterSwapMode,
     ) -> Result<Value> {
         let url = format!("{}/quote", self.base_url);
-        
+
         let mut params = HashMap::new();
         params.insert("inputMint", input_mint);
         params.insert("outputMint", output_mint);
         params.insert("amount", amount);
         params.insert("slippageBps", &slippage_bps.to_string());
+        params.insert("swapMode", swap_mode.as_str());
         params.insert("onlyDirectRoutes", "false");
         params.insert("asLegacyTransaction", "false");
-        
-        let response = self.client
-            .get(&url)
-            .query(&params)
-            .send()
-            .await?;
-        
+
+        let response = self.client.get(&url).query(&params).send().await?;
+
         if !response.status().is_success() {
             let error_text = response.text().await?;
             return Err(anyhow::anyhow!("Jupiter quote failed: {}", error_text));
         }
-        
+
         let quote: Value = response.json().await?;
         Ok(quote)
     }
-    
-    pub async fn get_swap_transaction(&self, quote_response: &Value) -> Result<SwapResponse> {
+
+    async fn get_swap_transaction(
+        &self,
+        quote_response: &Value,
+        user_public_key: &str,
+    ) -> Result<SwapResponse> {
         let url = format!("{}/swap", self.base_url);
-        
-        // Extract user public key from quote response or use a placeholder
-        // In a real implementation, this would be passed from the caller
-        let user_public_key = "11111111111111111111111111111112"; // Placeholder
-        
+
         let swap_request = SwapRequest {
             quote_response: quote_response.clone(),
             user_public_key: user_public_key.to_string(),
@@ -148,66 +202,253 @@ impl JupiterClient {
             use_token_ledger: false,
             destination_token_account: None,
         };
-        
-        let response = self.client
-            .post(&url)
-            .json(&swap_request)
-            .send()
-            .await?;
-        
+
+        let response = self.client.post(&url).json(&swap_request).send().await?;
+
         if !response.status().is_success() {
             let error_text = response.text().await?;
             return Err(anyhow::anyhow!("Jupiter swap failed: {}", error_text));
         }
-        
+
         let swap_response: SwapResponse = response.json().await?;
         Ok(swap_response)
     }
-    
-    pub async fn get_swap_transaction_with_user(
-        &self, 
-        quote_response: &Value, 
-        user_public_key: &str
+
+    async fn health_check(&self) -> Result<bool> {
+        let url = format!("{}/health", self.base_url);
+        let response = self.client.get(&url).send().await?;
+        Ok(response.status().is_success())
+    }
+}
+
+/// Offline backend that returns canned `QuoteResponse`/`SwapResponse` values
+/// instead of hitting the network. Used for local development and tests,
+/// e.g. `simulate_swap`'s quote-parsing and compute-budget-injection paths.
+pub struct MockJupiterBackend {
+    out_amount: String,
+}
+
+impl MockJupiterBackend {
+    pub fn new() -> Self {
+        Self {
+            out_amount: "1000000".to_string(),
+        }
+    }
+
+    pub fn with_out_amount(out_amount: impl Into<String>) -> Self {
+        Self {
+            out_amount: out_amount.into(),
+        }
+    }
+
+    /// A minimal, unsigned, empty-instruction transaction that is valid
+    /// to bincode-serialize and base64-encode, so callers that decode the
+    /// canned `swapTransaction` never hit an error path.
+    fn canned_swap_transaction() -> String {
+        use solana_sdk::{message::Message, pubkey::Pubkey, transaction::Transaction};
+
+        let message = Message::new(&[], Some(&Pubkey::default()));
+        let transaction = Transaction::new_unsigned(message);
+        let bytes = bincode::serialize(&transaction).expect("mock transaction always serializes");
+        base64::engine::general_purpose::STANDARD.encode(bytes)
+    }
+}
+
+#[async_trait]
+impl JupiterBackend for MockJupiterBackend {
+    async fn get_quote(
+        &self,
+        input_mint: &str,
+        output_mint: &str,
+        amount: &str,
+        slippage_bps: u16,
+        swap_mode: JupiterSwapMode,
+    ) -> Result<Value> {
+        // In ExactOut mode `amount` is the desired output, so the canned
+        // "other side" value represents our canned input/max-input instead
+        let (in_amount, out_amount) = match swap_mode {
+            JupiterSwapMode::ExactIn => (amount.to_string(), self.out_amount.clone()),
+            JupiterSwapMode::ExactOut => (self.out_amount.clone(), amount.to_string()),
+        };
+
+        Ok(json!({
+            "inputMint": input_mint,
+            "inAmount": in_amount,
+            "outputMint": output_mint,
+            "outAmount": out_amount,
+            "otherAmountThreshold": self.out_amount,
+            "swapMode": swap_mode.as_str(),
+            "slippageBps": slippage_bps,
+            "platformFee": Value::Null,
+            "priceImpactPct": "0",
+            "routePlan": [],
+            "contextSlot": 0,
+            "timeTaken": 0.0,
+        }))
+    }
+
+    async fn get_swap_transaction(
+        &self,
+        _quote_response: &Value,
+        _user_public_key: &str,
     ) -> Result<SwapResponse> {
-        let url = format!("{}/swap", self.base_url);
-        
-        let swap_request = SwapRequest {
-            quote_response: quote_response.clone(),
-            user_public_key: user_public_key.to_string(),
-            wrap_and_unwrap_sol: true,
-            use_shared_accounts: true,
-            fee_account: None,
-            tracking_account: None,
-            compute_unit_price_micro_lamports: None,
+        Ok(SwapResponse {
+            swap_transaction: Self::canned_swap_transaction(),
+            last_valid_block_height: 0,
             prioritization_fee_lamports: None,
-            as_legacy_transaction: false,
-            use_token_ledger: false,
-            destination_token_account: None,
-        };
-        
-        let response = self.client
-            .post(&url)
-            .json(&swap_request)
-            .send()
-            .await?;
-        
-        if !response.status().is_success() {
-            let error_text = response.text().await?;
-            return Err(anyhow::anyhow!("Jupiter swap failed: {}", error_text));
+            compute_unit_limit: None,
+            prioritization_type: None,
+            dynamic_slippage_report: None,
+            simulation_error: None,
+        })
+    }
+
+    async fn health_check(&self) -> Result<bool> {
+        Ok(true)
+    }
+}
+
+pub struct JupiterClient {
+    backend: Box<dyn JupiterBackend>,
+}
+
+impl JupiterClient {
+    /// Picks the live backend by default, or the mock backend when the
+    /// `MOCK_JUPITER` env var is set to a truthy value.
+    pub fn new() -> Self {
+        if Self::mock_requested() {
+            Self::new_mock()
+        } else {
+            Self::with_base_url("https://quote-api.jup.ag/v6".to_string())
+        }
+    }
+
+    pub fn with_base_url(base_url: String) -> Self {
+        Self {
+            backend: Box::new(LiveJupiterBackend::new(base_url)),
+        }
+    }
+
+    pub fn new_mock() -> Self {
+        Self {
+            backend: Box::new(MockJupiterBackend::new()),
         }
-        
-        let swap_response: SwapResponse = response.json().await?;
-        Ok(swap_response)
     }
-    
+
+    fn mock_requested() -> bool {
+        matches!(
+            std::env::var("MOCK_JUPITER").as_deref(),
+            Ok("1") | Ok("true") | Ok("TRUE")
+        )
+    }
+
+    pub async fn get_quote(
+        &self,
+        input_mint: &str,
+        output_mint: &str,
+        amount: &str,
+        slippage_bps: u16,
+        swap_mode: JupiterSwapMode,
+    ) -> Result<Value> {
+        self.backend
+            .get_quote(input_mint, output_mint, amount, slippage_bps, swap_mode)
+            .await
+    }
+
+    pub async fn get_swap_transaction(&self, quote_response: &Value) -> Result<SwapResponse> {
+        // Placeholder pubkey; callers that have a real wallet should use
+        // `get_swap_transaction_with_user` instead.
+        self.backend
+            .get_swap_transaction(quote_response, "11111111111111111111111111111112")
+            .await
+    }
+
+    pub async fn get_swap_transaction_with_user(
+        &self,
+        quote_response: &Value,
+        user_public_key: &str,
+    ) -> Result<SwapResponse> {
+        self.backend
+            .get_swap_transaction(quote_response, user_public_key)
+            .await
+    }
+
     pub async fn health_check(&self) -> Result<bool> {
-        let url = format!("{}/health", self.base_url);
-        
-        let response = self.client
-            .get(&url)
-            .send()
-            .await?;
-        
-        Ok(response.status().is_success())
+        self.backend.health_check().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_default_cli_swap_mode() {
+        // The CLI and `Serve`'s JSON-RPC params both default `swap_mode` to
+        // this exact hyphenated string; it must round-trip through `.parse()`
+        assert_eq!(
+            "exact-in".parse::<JupiterSwapMode>().unwrap(),
+            JupiterSwapMode::ExactIn
+        );
+    }
+
+    #[test]
+    fn parses_swap_mode_case_and_separator_insensitively() {
+        assert_eq!(
+            "ExactIn".parse::<JupiterSwapMode>().unwrap(),
+            JupiterSwapMode::ExactIn
+        );
+        assert_eq!(
+            "exact_out".parse::<JupiterSwapMode>().unwrap(),
+            JupiterSwapMode::ExactOut
+        );
+        assert_eq!(
+            "EXACT-OUT".parse::<JupiterSwapMode>().unwrap(),
+            JupiterSwapMode::ExactOut
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_swap_mode() {
+        assert!("sideways".parse::<JupiterSwapMode>().is_err());
+    }
+
+    #[tokio::test]
+    async fn mock_backend_quotes_exact_in_and_exact_out() {
+        let backend = MockJupiterBackend::with_out_amount("42");
+
+        let exact_in = backend
+            .get_quote("SOL", "USDC", "100", 50, JupiterSwapMode::ExactIn)
+            .await
+            .unwrap();
+        assert_eq!(exact_in["inAmount"], "100");
+        assert_eq!(exact_in["outAmount"], "42");
+
+        // In ExactOut mode the canned value is the max input spent, and the
+        // caller-supplied amount is the (fixed) desired output instead
+        let exact_out = backend
+            .get_quote("SOL", "USDC", "100", 50, JupiterSwapMode::ExactOut)
+            .await
+            .unwrap();
+        assert_eq!(exact_out["inAmount"], "42");
+        assert_eq!(exact_out["outAmount"], "100");
+    }
+
+    #[tokio::test]
+    async fn mock_backend_swap_transaction_decodes_as_versioned_transaction() {
+        use solana_sdk::transaction::VersionedTransaction;
+
+        let backend = MockJupiterBackend::new();
+        let quote = backend
+            .get_quote("SOL", "USDC", "100", 50, JupiterSwapMode::ExactIn)
+            .await
+            .unwrap();
+        let swap_response = backend.get_swap_transaction(&quote, "11111111111111111111111111111112").await.unwrap();
+
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(&swap_response.swap_transaction)
+            .unwrap();
+        let decoded: VersionedTransaction = bincode::deserialize(&bytes).unwrap();
+        assert!(decoded.message.instructions().is_empty());
     }
 }
@@ -3,10 +3,13 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 mod commands;
+mod fee_oracle;
+mod idempotency;
 mod jupiter;
+mod providers;
 mod utils;
 
-use commands::{ping::PingCommand, simulate::SimulateCommand, swap::SwapCommand};
+use commands::{ping::PingCommand, serve::ServeCommand, simulate::SimulateCommand, swap::SwapCommand};
 
 #[derive(Parser)]
 #[command(name = "exec-rs")]
@@ -39,23 +42,37 @@ enum Commands {
         #[arg(long)]
         output_mint: String,
         
-        /// Amount to swap
+        /// Amount to swap (decimal UI amount or raw base units)
         #[arg(long)]
         amount: String,
-        
+
+        /// Decimals of the input mint, used to convert `amount` to base units
+        #[arg(long, default_value = "9")]
+        input_decimals: u8,
+
         /// Slippage in basis points
         #[arg(long)]
         slippage_bps: u16,
-        
+
         /// RPC endpoint
         #[arg(long, default_value = "https://api.mainnet-beta.solana.com")]
         rpc_url: String,
-        
+
         /// Route info from Jupiter (JSON)
         #[arg(long)]
         route_info: Option<String>,
+
+        /// Swap route provider: "auto" (compare all), "jupiter", or "sanctum"
+        #[arg(long, default_value = "auto")]
+        route_provider: String,
+
+        /// Run the simulation offline against an in-process
+        /// solana-program-test BanksClient instead of the live RPC (also
+        /// enabled by setting the SIMULATE_BANKS env var)
+        #[arg(long, default_value_t = false)]
+        banks: bool,
     },
-    
+
     /// Execute a swap
     Swap {
         /// Input mint address
@@ -66,14 +83,23 @@ enum Commands {
         #[arg(long)]
         output_mint: String,
         
-        /// Amount to swap
+        /// Amount to swap (decimal UI amount or raw base units)
         #[arg(long)]
         amount: String,
-        
+
+        /// Decimals of the input mint, used to convert `amount` to base units
+        #[arg(long, default_value = "9")]
+        input_decimals: u8,
+
+        /// Swap mode: "exact-in" (spend exactly `amount`) or "exact-out"
+        /// (receive exactly `amount`, reporting the max input spent)
+        #[arg(long, default_value = "exact-in")]
+        swap_mode: String,
+
         /// Slippage in basis points
         #[arg(long)]
         slippage_bps: u16,
-        
+
         /// Wallet file path
         #[arg(long)]
         wallet: String,
@@ -93,14 +119,63 @@ enum Commands {
         /// Route info from Jupiter (JSON)
         #[arg(long)]
         route_info: Option<String>,
-        
-        /// Priority fee in microlamports
+
+        /// Swap route provider: "auto" (compare all), "jupiter", or "sanctum"
+        #[arg(long, default_value = "auto")]
+        route_provider: String,
+
+        /// Priority fee in microlamports. Overrides the fee oracle entirely
+        /// when set
         #[arg(long)]
         priority_fee: Option<u64>,
-        
+
         /// Compute unit limit
         #[arg(long)]
         compute_unit_limit: Option<u32>,
+
+        /// Multiplier applied to the fee oracle's recommended priority fee
+        #[arg(long, default_value = "1.0")]
+        fee_multiplier: f64,
+
+        /// Floor for the fee oracle's recommended priority fee, in microlamports
+        #[arg(long, default_value = "1")]
+        min_priority_fee: u64,
+
+        /// Ceiling for the fee oracle's recommended priority fee, in microlamports
+        #[arg(long, default_value = "1000000")]
+        max_priority_fee: u64,
+
+        /// Tip paid to the Jito validator for bundle inclusion, in lamports
+        /// (only used when `mode = "jito"`)
+        #[arg(long, default_value = "10000")]
+        jito_tip_lamports: u64,
+
+        /// Jito block-engine bundles endpoint (only used when `mode = "jito"`)
+        #[arg(long, default_value = "https://mainnet.block-engine.jito.wtf/api/v1/bundles")]
+        jito_block_engine_url: String,
+
+        /// Directory the idempotency store persists completed results to,
+        /// keyed by `--idempotency-key`. Only consulted when that flag is set
+        #[arg(long, default_value = ".idempotency")]
+        idempotency_dir: String,
+    },
+
+    /// Start a persistent JSON-RPC server exposing ping/simulate/swap over a
+    /// shared RPC client, avoiding per-call process and client startup cost
+    Serve {
+        /// Address to bind the JSON-RPC HTTP server to
+        #[arg(long, default_value = "127.0.0.1:8899")]
+        bind_addr: String,
+
+        /// RPC endpoint shared across every request the server handles
+        #[arg(long, default_value = "https://api.mainnet-beta.solana.com")]
+        rpc_url: String,
+
+        /// Directory the idempotency store persists completed results to.
+        /// Fixed for the life of the server rather than taken per-request,
+        /// since callers cannot be trusted to name an arbitrary directory
+        #[arg(long, default_value = ".idempotency")]
+        idempotency_dir: String,
     },
 }
 
@@ -113,8 +188,12 @@ struct ExecutorResult {
     error: Option<String>,
     logs: Option<Vec<String>>,
     expected_out: Option<String>,
+    simulated_out: Option<String>,
+    realized_price_impact_pct: Option<f64>,
     compute_units_used: Option<u32>,
     idempotency_key: Option<String>,
+    priority_fee_micro_lamports: Option<u64>,
+    max_input_amount: Option<String>,
 }
 
 #[tokio::main]
@@ -122,8 +201,35 @@ async fn main() -> anyhow::Result<()> {
     env_logger::init();
     
     let cli = Cli::parse();
-    
-    let result = match cli.command {
+
+    // `Serve` runs forever and doesn't produce a single `ExecutorResult` to
+    // print, so it's handled separately from the request/response commands
+    if let Commands::Serve {
+        bind_addr,
+        rpc_url,
+        idempotency_dir,
+    } = cli.command
+    {
+        let serve_cmd = ServeCommand::new(bind_addr, rpc_url, idempotency_dir);
+        return serve_cmd.execute().await;
+    }
+
+    let result = run_command(cli.command).await?;
+
+    // Output result as JSON
+    let json_output = serde_json::to_string_pretty(&result)?;
+    println!("{}", json_output);
+
+    if !result.success {
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+async fn run_command(command: Commands) -> anyhow::Result<ExecutorResult> {
+    match command {
+        Commands::Serve { .. } => unreachable!("Serve is handled in main before run_command"),
         Commands::Ping { rpc_url, timeout } => {
             let ping_cmd = PingCommand::new(rpc_url, timeout);
             ping_cmd.execute().await
@@ -133,58 +239,72 @@ async fn main() -> anyhow::Result<()> {
             input_mint,
             output_mint,
             amount,
+            input_decimals,
             slippage_bps,
             rpc_url,
             route_info,
+            route_provider,
+            banks,
         } => {
             let simulate_cmd = SimulateCommand::new(
                 input_mint,
                 output_mint,
                 amount,
+                input_decimals,
                 slippage_bps,
                 rpc_url,
                 route_info,
+                route_provider,
+                banks,
             );
             simulate_cmd.execute().await
         }
-        
+
         Commands::Swap {
             input_mint,
             output_mint,
             amount,
+            input_decimals,
+            swap_mode,
             slippage_bps,
             wallet,
             rpc_url,
             mode,
             idempotency_key,
             route_info,
+            route_provider,
             priority_fee,
             compute_unit_limit,
+            fee_multiplier,
+            min_priority_fee,
+            max_priority_fee,
+            jito_tip_lamports,
+            jito_block_engine_url,
+            idempotency_dir,
         } => {
             let swap_cmd = SwapCommand::new(
                 input_mint,
                 output_mint,
                 amount,
+                input_decimals,
+                swap_mode.parse()?,
                 slippage_bps,
                 wallet,
                 rpc_url,
                 mode,
                 idempotency_key,
                 route_info,
+                route_provider,
                 priority_fee,
                 compute_unit_limit,
+                fee_multiplier,
+                min_priority_fee,
+                max_priority_fee,
+                jito_tip_lamports,
+                jito_block_engine_url,
+                idempotency_dir,
             );
             swap_cmd.execute().await
         }
-    };
-    
-    // Output result as JSON
-    let json_output = serde_json::to_string_pretty(&result)?;
-    println!("{}", json_output);
-    
-    if !result.success {
-        std::process::exit(1);
     }
-    
-    Ok(())
 }
@@ -0,0 +1,176 @@
+pub mod sanctum;
+
+use crate::jupiter::{JupiterClient, JupiterSwapMode, SwapResponse};
+use anyhow::Result;
+use async_trait::async_trait;
+use serde_json::Value;
+
+pub use sanctum::SanctumClient;
+
+/// Common shape for a signable swap transaction, independent of which
+/// aggregator produced it.
+pub struct SwapTransactionResponse {
+    pub swap_transaction: String,
+    pub last_valid_block_height: u64,
+}
+
+impl From<SwapResponse> for SwapTransactionResponse {
+    fn from(response: SwapResponse) -> Self {
+        Self {
+            swap_transaction: response.swap_transaction,
+            last_valid_block_height: response.last_valid_block_height,
+        }
+    }
+}
+
+/// A swap route aggregator (Jupiter, Sanctum, ...). `SimulateCommand` and
+/// the swap executors compare quotes across every configured provider and
+/// keep the best one per [`quote_rank`] (highest `outAmount` for `ExactIn`,
+/// lowest input spent for `ExactOut`), rather than hardwiring a single
+/// vendor.
+#[async_trait]
+pub trait SwapProvider: Send + Sync {
+    /// Short identifier used in logs, e.g. "jupiter".
+    fn name(&self) -> &'static str;
+
+    async fn get_quote(
+        &self,
+        input_mint: &str,
+        output_mint: &str,
+        amount: &str,
+        slippage_bps: u16,
+        swap_mode: JupiterSwapMode,
+    ) -> Result<Value>;
+
+    async fn get_swap_transaction(
+        &self,
+        quote_response: &Value,
+        user_public_key: &str,
+    ) -> Result<SwapTransactionResponse>;
+}
+
+#[async_trait]
+impl SwapProvider for JupiterClient {
+    fn name(&self) -> &'static str {
+        "jupiter"
+    }
+
+    async fn get_quote(
+        &self,
+        input_mint: &str,
+        output_mint: &str,
+        amount: &str,
+        slippage_bps: u16,
+        swap_mode: JupiterSwapMode,
+    ) -> Result<Value> {
+        JupiterClient::get_quote(self, input_mint, output_mint, amount, slippage_bps, swap_mode)
+            .await
+    }
+
+    async fn get_swap_transaction(
+        &self,
+        quote_response: &Value,
+        user_public_key: &str,
+    ) -> Result<SwapTransactionResponse> {
+        JupiterClient::get_swap_transaction_with_user(self, quote_response, user_public_key)
+            .await
+            .map(Into::into)
+    }
+}
+
+/// Parses a quote's `outAmount` field as a `u128` for comparison; routes
+/// that omit it or return garbage sort last rather than failing the whole
+/// lookup.
+fn out_amount(quote: &Value) -> u128 {
+    quote
+        .get("outAmount")
+        .and_then(Value::as_str)
+        .and_then(|s| s.parse::<u128>().ok())
+        .unwrap_or(0)
+}
+
+/// Parses a quote's `otherAmountThreshold` field as a `u128`. In
+/// `ExactOut` mode this is the maximum input the quote would spend, so a
+/// missing or unparseable value sorts last by treating it as unbounded.
+fn other_amount_threshold(quote: &Value) -> u128 {
+    quote
+        .get("otherAmountThreshold")
+        .and_then(Value::as_str)
+        .and_then(|s| s.parse::<u128>().ok())
+        .unwrap_or(u128::MAX)
+}
+
+/// Ranks a quote so a higher rank always means "better", regardless of
+/// `swap_mode`: in `ExactIn` mode `outAmount` is the variable side, so the
+/// highest output wins; in `ExactOut` mode `outAmount` is the fixed
+/// desired output and `otherAmountThreshold` is the variable side, so the
+/// lowest input spent wins instead.
+fn quote_rank(quote: &Value, swap_mode: JupiterSwapMode) -> i128 {
+    match swap_mode {
+        JupiterSwapMode::ExactIn => out_amount(quote) as i128,
+        JupiterSwapMode::ExactOut => {
+            -i128::try_from(other_amount_threshold(quote)).unwrap_or(i128::MAX)
+        }
+    }
+}
+
+/// Fetches a quote from every provider and returns the provider index and
+/// the best quote per [`quote_rank`]. Providers that error are skipped; an
+/// error is only returned if every provider failed.
+pub async fn best_quote(
+    providers: &[&dyn SwapProvider],
+    input_mint: &str,
+    output_mint: &str,
+    amount: &str,
+    slippage_bps: u16,
+    swap_mode: JupiterSwapMode,
+) -> Result<(usize, Value)> {
+    let mut best: Option<(usize, Value)> = None;
+
+    for (index, provider) in providers.iter().enumerate() {
+        match provider
+            .get_quote(input_mint, output_mint, amount, slippage_bps, swap_mode)
+            .await
+        {
+            Ok(quote) => {
+                let is_better = best
+                    .as_ref()
+                    .map(|(_, best_quote)| {
+                        quote_rank(&quote, swap_mode) > quote_rank(best_quote, swap_mode)
+                    })
+                    .unwrap_or(true);
+                if is_better {
+                    best = Some((index, quote));
+                }
+            }
+            Err(e) => {
+                log::warn!("{} quote failed: {}", provider.name(), e);
+            }
+        }
+    }
+
+    best.ok_or_else(|| anyhow::anyhow!("all swap providers failed to return a quote"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn ranks_exact_in_by_highest_out_amount() {
+        let worse = json!({"outAmount": "100"});
+        let better = json!({"outAmount": "200"});
+        assert!(quote_rank(&better, JupiterSwapMode::ExactIn) > quote_rank(&worse, JupiterSwapMode::ExactIn));
+    }
+
+    #[test]
+    fn ranks_exact_out_by_lowest_input_spent() {
+        let cheaper = json!({"outAmount": "100", "otherAmountThreshold": "150"});
+        let pricier = json!({"outAmount": "100", "otherAmountThreshold": "200"});
+        assert!(
+            quote_rank(&cheaper, JupiterSwapMode::ExactOut)
+                > quote_rank(&pricier, JupiterSwapMode::ExactOut)
+        );
+    }
+}
@@ -0,0 +1,131 @@
+use super::SwapTransactionResponse;
+use crate::jupiter::JupiterSwapMode;
+use anyhow::Result;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Client for Sanctum's LST swap API. Mirrors the shape of `JupiterClient`
+/// so both can be driven through the same `SwapProvider` trait: Sanctum
+/// routes SOL/LST and LST/LST pairs (e.g. mSOL, jitoSOL, bSOL) and often
+/// beats Jupiter's pricing on those pairs.
+pub struct SanctumClient {
+    client: Client,
+    base_url: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SanctumSwapRequest {
+    #[serde(rename = "quoteResponse")]
+    quote_response: Value,
+    #[serde(rename = "signer")]
+    signer: String,
+}
+
+impl SanctumClient {
+    pub fn new() -> Self {
+        Self::with_base_url("https://api.sanctum.so/v1".to_string())
+    }
+
+    pub fn with_base_url(base_url: String) -> Self {
+        Self {
+            client: Client::new(),
+            base_url,
+        }
+    }
+
+    pub async fn get_quote(
+        &self,
+        input_mint: &str,
+        output_mint: &str,
+        amount: &str,
+        slippage_bps: u16,
+        swap_mode: JupiterSwapMode,
+    ) -> Result<Value> {
+        if swap_mode == JupiterSwapMode::ExactOut {
+            return Err(anyhow::anyhow!("Sanctum does not support ExactOut quotes"));
+        }
+
+        let url = format!("{}/swap/quote", self.base_url);
+
+        let mut params = HashMap::new();
+        params.insert("input", input_mint);
+        params.insert("outputLstMint", output_mint);
+        params.insert("amount", amount);
+        params.insert("slippageBps", &slippage_bps.to_string());
+
+        let response = self.client.get(&url).query(&params).send().await?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await?;
+            return Err(anyhow::anyhow!("Sanctum quote failed: {}", error_text));
+        }
+
+        let quote: Value = response.json().await?;
+        Ok(quote)
+    }
+
+    pub async fn get_swap_transaction(
+        &self,
+        quote_response: &Value,
+        user_public_key: &str,
+    ) -> Result<SwapTransactionResponse> {
+        let url = format!("{}/swap/instructions", self.base_url);
+
+        let swap_request = SanctumSwapRequest {
+            quote_response: quote_response.clone(),
+            signer: user_public_key.to_string(),
+        };
+
+        let response = self.client.post(&url).json(&swap_request).send().await?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await?;
+            return Err(anyhow::anyhow!("Sanctum swap failed: {}", error_text));
+        }
+
+        let body: Value = response.json().await?;
+        let swap_transaction = body
+            .get("swapTransaction")
+            .and_then(Value::as_str)
+            .ok_or_else(|| anyhow::anyhow!("Sanctum swap response missing swapTransaction"))?
+            .to_string();
+        let last_valid_block_height = body
+            .get("lastValidBlockHeight")
+            .and_then(Value::as_u64)
+            .unwrap_or(0);
+
+        Ok(SwapTransactionResponse {
+            swap_transaction,
+            last_valid_block_height,
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl super::SwapProvider for SanctumClient {
+    fn name(&self) -> &'static str {
+        "sanctum"
+    }
+
+    async fn get_quote(
+        &self,
+        input_mint: &str,
+        output_mint: &str,
+        amount: &str,
+        slippage_bps: u16,
+        swap_mode: JupiterSwapMode,
+    ) -> Result<Value> {
+        SanctumClient::get_quote(self, input_mint, output_mint, amount, slippage_bps, swap_mode)
+            .await
+    }
+
+    async fn get_swap_transaction(
+        &self,
+        quote_response: &Value,
+        user_public_key: &str,
+    ) -> Result<SwapTransactionResponse> {
+        SanctumClient::get_swap_transaction(self, quote_response, user_public_key).await
+    }
+}
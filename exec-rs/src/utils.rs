@@ -1,49 +1,158 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use solana_sdk::signature::Keypair;
 use std::fs;
+use std::str::FromStr;
 
-/// Load wallet keypair from file
+/// Solana's standard derivation path, matching what the Solana CLI and
+/// most wallets (Phantom, Solflare, ...) use to derive the first account
+/// from a mnemonic.
+const DEFAULT_DERIVATION_PATH: &str = "m/44'/501'/0'/0'";
+
+/// Load wallet keypair from file. Accepts, in order: the Solana CLI's
+/// 64-byte JSON array, a raw 64-byte secret key, a base58-encoded secret
+/// key (32 or 64 bytes, as exported by wallets like Phantom), and a BIP39
+/// mnemonic seed phrase derived via `m/44'/501'/0'/0'`.
 pub fn load_wallet(wallet_path: &str) -> Result<Keypair> {
     let wallet_data = fs::read(wallet_path)?;
-    
-    // Try to parse as JSON array first (Solana CLI format)
-    if let Ok(json_data) = serde_json::from_slice::<Vec<u8>>(&wallet_data) {
-        if json_data.len() == 64 {
-            let keypair = Keypair::from_bytes(&json_data)?;
-            return Ok(keypair);
-        }
+    let mut attempted = Vec::new();
+
+    match load_wallet_from_json_array(&wallet_data) {
+        Ok(keypair) => return Ok(keypair),
+        Err(e) => attempted.push(format!("JSON array ({})", e)),
     }
-    
-    // Try to parse as raw bytes
+
     if wallet_data.len() == 64 {
-        let keypair = Keypair::from_bytes(&wallet_data)?;
-        return Ok(keypair);
+        match Keypair::from_bytes(&wallet_data) {
+            Ok(keypair) => return Ok(keypair),
+            Err(e) => attempted.push(format!("raw 64-byte secret key ({})", e)),
+        }
     }
-    
-    Err(anyhow::anyhow!("Invalid wallet file format"))
+
+    let text = String::from_utf8_lossy(&wallet_data).trim().to_string();
+
+    match load_wallet_from_base58(&text) {
+        Ok(keypair) => return Ok(keypair),
+        Err(e) => attempted.push(format!("base58 secret key ({})", e)),
+    }
+
+    match load_wallet_from_mnemonic(&text) {
+        Ok(keypair) => return Ok(keypair),
+        Err(e) => attempted.push(format!("BIP39 mnemonic ({})", e)),
+    }
+
+    Err(anyhow::anyhow!(
+        "Invalid wallet file format, tried: {}",
+        attempted.join("; ")
+    ))
 }
 
-/// Format lamports as SOL
-pub fn lamports_to_sol(lamports: u64) -> f64 {
-    lamports as f64 / 1_000_000_000.0
+fn load_wallet_from_json_array(data: &[u8]) -> Result<Keypair> {
+    let json_data = serde_json::from_slice::<Vec<u8>>(data).context("not a JSON byte array")?;
+    if json_data.len() != 64 {
+        return Err(anyhow::anyhow!(
+            "JSON array has {} bytes, expected 64",
+            json_data.len()
+        ));
+    }
+    Ok(Keypair::from_bytes(&json_data)?)
 }
 
-/// Format SOL as lamports
-pub fn sol_to_lamports(sol: f64) -> u64 {
-    (sol * 1_000_000_000.0) as u64
+fn load_wallet_from_base58(text: &str) -> Result<Keypair> {
+    let decoded = bs58::decode(text).into_vec().context("not valid base58")?;
+
+    match decoded.len() {
+        64 => Ok(Keypair::from_bytes(&decoded)?),
+        32 => {
+            let secret = ed25519_dalek::SecretKey::from_bytes(&decoded)?;
+            let public = ed25519_dalek::PublicKey::from(&secret);
+
+            let mut keypair_bytes = [0u8; 64];
+            keypair_bytes[..32].copy_from_slice(&decoded);
+            keypair_bytes[32..].copy_from_slice(public.as_bytes());
+            Ok(Keypair::from_bytes(&keypair_bytes)?)
+        }
+        other => Err(anyhow::anyhow!(
+            "base58 secret key has {} bytes, expected 32 or 64",
+            other
+        )),
+    }
 }
 
-/// Parse amount string (supports both lamports and SOL)
-pub fn parse_amount(amount_str: &str) -> Result<u64> {
-    if amount_str.contains('.') {
-        // Assume SOL amount
-        let sol_amount: f64 = amount_str.parse()?;
-        Ok(sol_to_lamports(sol_amount))
+fn load_wallet_from_mnemonic(text: &str) -> Result<Keypair> {
+    if text.split_whitespace().count() < 12 {
+        return Err(anyhow::anyhow!("does not look like a mnemonic phrase"));
+    }
+
+    let mnemonic = bip39::Mnemonic::parse(text).context("invalid BIP39 mnemonic")?;
+    let seed = mnemonic.to_seed("");
+
+    let derivation_path = ed25519_dalek_bip32::DerivationPath::from_str(DEFAULT_DERIVATION_PATH)?;
+    let extended = ed25519_dalek_bip32::ExtendedSecretKey::from_seed(&seed)?
+        .derive(&derivation_path)?;
+
+    let secret = extended.secret_key;
+    let public = ed25519_dalek::PublicKey::from(&secret);
+
+    let mut keypair_bytes = [0u8; 64];
+    keypair_bytes[..32].copy_from_slice(&secret.to_bytes());
+    keypair_bytes[32..].copy_from_slice(public.as_bytes());
+    Ok(Keypair::from_bytes(&keypair_bytes)?)
+}
+
+/// Parse a decimal (or already-base-unit) amount string into a base-unit
+/// `u64`, given the mint's `decimals`, with no floating point involved.
+/// A string containing `.` is treated as a human-readable amount (e.g.
+/// SOL, or a token's UI amount); the fractional part is left-padded or
+/// truncated to `decimals` digits rather than rounded through `f64`. A
+/// string with no `.` is treated as already being in base units.
+pub fn parse_amount_with_decimals(amount_str: &str, decimals: u8) -> Result<u64> {
+    let amount_str = amount_str.trim();
+
+    let Some((whole, frac)) = amount_str.split_once('.') else {
+        return amount_str
+            .parse::<u64>()
+            .with_context(|| format!("invalid base-unit amount: {}", amount_str));
+    };
+
+    if !whole.chars().all(|c| c.is_ascii_digit()) && !whole.is_empty() {
+        return Err(anyhow::anyhow!("invalid amount: {}", amount_str));
+    }
+    if !frac.chars().all(|c| c.is_ascii_digit()) {
+        return Err(anyhow::anyhow!("invalid amount: {}", amount_str));
+    }
+
+    let whole_part: u128 = if whole.is_empty() {
+        0
     } else {
-        // Assume lamports
-        let lamports: u64 = amount_str.parse()?;
-        Ok(lamports)
+        whole.parse().with_context(|| format!("invalid amount: {}", amount_str))?
+    };
+
+    let decimals = decimals as usize;
+    let mut frac_digits = frac.to_string();
+    if frac_digits.len() > decimals {
+        frac_digits.truncate(decimals);
+    } else {
+        frac_digits.push_str(&"0".repeat(decimals - frac_digits.len()));
     }
+    let frac_part: u128 = if frac_digits.is_empty() {
+        0
+    } else {
+        frac_digits.parse().with_context(|| format!("invalid amount: {}", amount_str))?
+    };
+
+    let base: u128 = 10u128.pow(decimals as u32);
+    let total = whole_part
+        .checked_mul(base)
+        .and_then(|v| v.checked_add(frac_part))
+        .ok_or_else(|| anyhow::anyhow!("amount overflows base units: {}", amount_str))?;
+
+    u64::try_from(total).map_err(|_| anyhow::anyhow!("amount exceeds u64 range: {}", amount_str))
+}
+
+/// Parse amount string (supports both lamports and SOL). Thin wrapper over
+/// [`parse_amount_with_decimals`] for SOL's 9 decimals.
+pub fn parse_amount(amount_str: &str) -> Result<u64> {
+    parse_amount_with_decimals(amount_str, 9)
 }
 
 /// Validate Solana public key format
@@ -84,24 +193,75 @@ pub fn format_duration(duration_ms: u64) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
-    
-    #[test]
-    fn test_lamports_conversion() {
-        assert_eq!(lamports_to_sol(1_000_000_000), 1.0);
-        assert_eq!(sol_to_lamports(1.0), 1_000_000_000);
-    }
-    
+
     #[test]
     fn test_parse_amount() {
         assert_eq!(parse_amount("1000000000").unwrap(), 1_000_000_000);
         assert_eq!(parse_amount("1.0").unwrap(), 1_000_000_000);
         assert_eq!(parse_amount("0.5").unwrap(), 500_000_000);
     }
-    
+
+    #[test]
+    fn test_parse_amount_with_decimals() {
+        // USDC has 6 decimals
+        assert_eq!(parse_amount_with_decimals("1.5", 6).unwrap(), 1_500_000);
+        assert_eq!(parse_amount_with_decimals("1000000", 6).unwrap(), 1_000_000);
+        // fractional digits beyond `decimals` are truncated, not rounded
+        assert_eq!(parse_amount_with_decimals("1.1234567", 6).unwrap(), 1_123_456);
+        assert_eq!(parse_amount_with_decimals(".5", 6).unwrap(), 500_000);
+    }
+
     #[test]
     fn test_format_duration() {
         assert_eq!(format_duration(500), "500ms");
         assert_eq!(format_duration(1500), "1.5s");
         assert_eq!(format_duration(65000), "1.1m");
     }
+
+    // Known vectors so a wrong byte-slice or derivation bug here would fail
+    // loudly instead of silently producing the wrong signing wallet.
+    #[test]
+    fn load_wallet_from_base58_64_byte_secret_derives_known_pubkey() {
+        use solana_sdk::signer::Signer;
+
+        let keypair = load_wallet_from_base58(
+            "99eUso3aSbE9tqGSTXzo3TLfKb9RkMTURrHKQ1K7Zh3StnzFNUx8FKCPPPPpR479qsw5zv2WNBKmgiz7WqgAJfM",
+        )
+        .unwrap();
+
+        assert_eq!(
+            keypair.pubkey().to_string(),
+            "GmaDrppBC7P5ARKV8g3djiwP89vz1jLK23V2GBjuAEGB"
+        );
+    }
+
+    #[test]
+    fn load_wallet_from_base58_32_byte_secret_derives_known_pubkey() {
+        use solana_sdk::signer::Signer;
+
+        let keypair =
+            load_wallet_from_base58("US517G5965aydkZ46HS38QLi7UQiSojurfbQfKCELFx").unwrap();
+
+        // Same underlying 32-byte secret as the 64-byte vector above, so it
+        // derives the same pubkey
+        assert_eq!(
+            keypair.pubkey().to_string(),
+            "GmaDrppBC7P5ARKV8g3djiwP89vz1jLK23V2GBjuAEGB"
+        );
+    }
+
+    #[test]
+    fn load_wallet_from_mnemonic_derives_known_pubkey_at_default_path() {
+        use solana_sdk::signer::Signer;
+
+        let keypair = load_wallet_from_mnemonic(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+        )
+        .unwrap();
+
+        assert_eq!(
+            keypair.pubkey().to_string(),
+            "HAgk14JpMQLgt6rVgv7cBQFJWFto5Dqxi472uT3DKpqk"
+        );
+    }
 }